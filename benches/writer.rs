@@ -0,0 +1,88 @@
+// Copyright 2015 The GeoRust Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Compares writing large `LineString`s with formatting each number with `{}`.
+//!
+//! Run with `cargo bench --features ryu` to also measure the writer with
+//! `NumberFormat::Ryu`.
+
+#[macro_use]
+extern crate criterion;
+extern crate wkt;
+
+use criterion::{black_box, BenchmarkId, Criterion, Throughput};
+use std::fmt::Write;
+use wkt::types::{Coord, LineString};
+#[cfg(feature = "ryu")]
+use wkt::{NumberFormat, WriteWkt, WriterOptions};
+
+/// A `LineString` of `len` coordinates with full-precision values, like projected or
+/// transformed data has.
+fn line_string(len: usize) -> LineString<f64> {
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    let mut next = || {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (state >> 11) as f64 / (1u64 << 53) as f64
+    };
+    LineString(
+        (0..len)
+            .map(|_| Coord {
+                x: next() * 360.0 - 180.0,
+                y: next() * 180.0 - 90.0,
+                z: None,
+                m: None,
+            })
+            .collect(),
+    )
+}
+
+fn write_line_string(c: &mut Criterion) {
+    let mut group = c.benchmark_group("write_line_string");
+    for len in [1_000, 100_000].iter() {
+        let line = line_string(*len);
+        group.throughput(Throughput::Elements(*len as u64));
+        group.bench_with_input(BenchmarkId::new("display", len), &line, |b, line| {
+            b.iter(|| {
+                let mut text = String::from("LINESTRING (");
+                for (i, coord) in line.0.iter().enumerate() {
+                    if i > 0 {
+                        text.push_str(", ");
+                    }
+                    write!(text, "{} {}", coord.x, coord.y).unwrap();
+                }
+                text.push(')');
+                black_box(text)
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("writer", len), &line, |b, line| {
+            b.iter(|| black_box(line.to_string()))
+        });
+        #[cfg(feature = "ryu")]
+        {
+            let options = WriterOptions {
+                number_format: NumberFormat::Ryu,
+                ..Default::default()
+            };
+            group.bench_with_input(BenchmarkId::new("writer_ryu", len), &line, |b, line| {
+                b.iter(|| black_box(line.with_options(&options).to_string()))
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, write_line_string);
+criterion_main!(benches);
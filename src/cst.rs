@@ -0,0 +1,624 @@
+// Copyright 2015 The GeoRust Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A concrete syntax tree of WKT text, for tools such as formatters and editors that need to
+//! keep the input exactly as it was written.
+//!
+//! Every byte of the input, whitespace included, belongs to exactly one token of the tree, so
+//! printing a `Cst` gives back the text it was parsed from. Building the tree never fails:
+//! anything the WKT grammar does not expect is kept as a token where it was found, and only
+//! lowering the tree to `Geometry` values reports errors. Lists nested deeper than
+//! `MAX_LIST_DEPTH` are kept whole as a single error token, so that neither building nor
+//! dropping the tree can overflow the stack.
+
+use std::fmt;
+
+use tokenizer::{Span, Token, Tokens};
+use Error;
+use Geometry;
+use ParseOptions;
+use Position;
+use Separator;
+use Wkt;
+use WktFloat;
+use DEFAULT_MAX_DEPTH;
+
+/// How deep lists are nested in a `Cst` before the rest of a list is kept as an error token.
+/// This is deep enough for anything the parser accepts with the default
+/// `ParseOptions::max_depth`, whose innermost geometry can nest three lists.
+pub const MAX_LIST_DEPTH: usize = DEFAULT_MAX_DEPTH + 2;
+
+/// The kinds of tokens in a `Cst`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SyntaxKind {
+    /// A run of spaces, tabs and line breaks.
+    Whitespace,
+    /// A keyword, such as `POINT`, `Z` or `EMPTY`, or any other character the tokenizer does
+    /// not recognize, such as `;`.
+    Word,
+    Number,
+    Comma,
+    ParenOpen,
+    ParenClose,
+    /// Text the tokenizer could not read, such as an invalid number, or a list nested deeper
+    /// than `MAX_LIST_DEPTH`.
+    Error,
+}
+
+/// The kinds of nodes in a `Cst`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NodeKind {
+    /// A geometry, from its type keyword through its optional dimension keyword to `EMPTY` or
+    /// its parenthesized list.
+    Geometry,
+    /// A parenthesized, comma separated list of coordinates, lists or geometries.
+    List,
+    /// The numbers of a single coordinate.
+    Coord,
+}
+
+/// A token of a `Cst`, holding its text as it appeared in the input.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SyntaxToken<'a> {
+    kind: SyntaxKind,
+    text: &'a str,
+    offset: usize,
+}
+
+impl<'a> SyntaxToken<'a> {
+    pub fn kind(&self) -> SyntaxKind {
+        self.kind
+    }
+
+    pub fn text(&self) -> &'a str {
+        self.text
+    }
+
+    /// The byte range of the input the token covers.
+    pub fn span(&self) -> Span {
+        Span {
+            start: self.offset,
+            end: self.offset + self.text.len(),
+        }
+    }
+}
+
+impl<'a> fmt::Display for SyntaxToken<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.text)
+    }
+}
+
+/// A child of a node or of the root of a `Cst`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SyntaxElement<'a> {
+    Node(SyntaxNode<'a>),
+    Token(SyntaxToken<'a>),
+}
+
+impl<'a> SyntaxElement<'a> {
+    /// The byte range of the input the element covers.
+    pub fn span(&self) -> Span {
+        match *self {
+            SyntaxElement::Node(ref node) => node.span(),
+            SyntaxElement::Token(ref token) => token.span(),
+        }
+    }
+}
+
+impl<'a> fmt::Display for SyntaxElement<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SyntaxElement::Node(ref node) => node.fmt(f),
+            SyntaxElement::Token(ref token) => token.fmt(f),
+        }
+    }
+}
+
+/// A node of a `Cst`. A node starts and ends with a token other than whitespace; whitespace
+/// around it belongs to its parent.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SyntaxNode<'a> {
+    kind: NodeKind,
+    children: Vec<SyntaxElement<'a>>,
+    input: &'a str,
+    span: Span,
+}
+
+impl<'a> SyntaxNode<'a> {
+    pub fn kind(&self) -> NodeKind {
+        self.kind
+    }
+
+    pub fn children(&self) -> &[SyntaxElement<'a>] {
+        &self.children
+    }
+
+    /// The child nodes, skipping the tokens.
+    pub fn nodes(&self) -> impl Iterator<Item = &SyntaxNode<'a>> {
+        self.children.iter().filter_map(|child| match *child {
+            SyntaxElement::Node(ref node) => Some(node),
+            SyntaxElement::Token(_) => None,
+        })
+    }
+
+    /// The child tokens other than whitespace, skipping the nodes.
+    pub fn tokens(&self) -> impl Iterator<Item = &SyntaxToken<'a>> {
+        self.children.iter().filter_map(|child| match *child {
+            SyntaxElement::Token(ref token) if token.kind != SyntaxKind::Whitespace => Some(token),
+            _ => None,
+        })
+    }
+
+    /// The byte range of the input the node covers.
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// The input text the node covers.
+    pub fn text(&self) -> &'a str {
+        &self.input[self.span.start..self.span.end]
+    }
+
+    /// Parses the text of the node as a single geometry with the settings in `options`, whose
+    /// limits then apply to this geometry alone. Errors are reported at their positions in the
+    /// whole input.
+    pub fn to_geometry<T: WktFloat>(&self, options: &ParseOptions) -> Result<Geometry<T>, Error> {
+        let parser = ::Parser::with_options(options.clone());
+        match parser.parse(self.text()) {
+            Ok(mut parsed) => match parsed.wkt.items.pop() {
+                Some(geometry) => Ok(geometry),
+                None => unreachable!(),
+            },
+            Err(mut err) => {
+                let base = Position::from_offset(self.input, self.span.start);
+                let position = err.position().within(base);
+                *err.position_mut() = position;
+                Err(err)
+            }
+        }
+    }
+}
+
+impl<'a> fmt::Display for SyntaxNode<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.text())
+    }
+}
+
+/// A concrete syntax tree of WKT text holding any number of geometries.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cst<'a> {
+    input: &'a str,
+    children: Vec<SyntaxElement<'a>>,
+}
+
+impl<'a> Cst<'a> {
+    /// Builds the tree of `input`.
+    pub fn parse(input: &'a str) -> Self {
+        let mut parser = Parser {
+            input,
+            tokens: lex(input),
+            next: 0,
+            depth: 0,
+        };
+        let mut children = Vec::new();
+        while let Some(kind) = parser.peek_kind(false) {
+            match kind {
+                SyntaxKind::Word => children.push(parser.geometry()),
+                _ => parser.bump(&mut children),
+            }
+        }
+        Cst { input, children }
+    }
+
+    /// The text the tree was parsed from.
+    pub fn text(&self) -> &'a str {
+        self.input
+    }
+
+    /// The top-level geometries and tokens, including whitespace.
+    pub fn children(&self) -> &[SyntaxElement<'a>] {
+        &self.children
+    }
+
+    /// The top-level geometry nodes, skipping the tokens.
+    pub fn geometries(&self) -> impl Iterator<Item = &SyntaxNode<'a>> {
+        self.children.iter().filter_map(|child| match *child {
+            SyntaxElement::Node(ref node) if node.kind == NodeKind::Geometry => Some(node),
+            _ => None,
+        })
+    }
+
+    /// Lowers the tree to the geometries it holds with the settings in `options`, failing on
+    /// the first one that is invalid or on anything between them other than whitespace. This
+    /// parses the whole input the way `Parser::many` does with `Separator::Whitespace`.
+    pub fn to_wkt<T: WktFloat>(&self, options: &ParseOptions) -> Result<Wkt<T>, Error> {
+        let parser = ::Parser::with_options(options.clone()).many(Separator::Whitespace);
+        parser.parse(self.input).map(|parsed| parsed.wkt)
+    }
+}
+
+impl<'a> fmt::Display for Cst<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for child in &self.children {
+            child.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Splits `input` into tokens, filling the gaps `Tokens` skips with whitespace tokens.
+fn lex(input: &str) -> Vec<SyntaxToken<'_>> {
+    let mut tokens = Tokens::new(input);
+    let mut lexed = Vec::new();
+    let mut end = 0;
+    loop {
+        let token = tokens.next();
+        let span = tokens.last_span();
+        if span.start > end {
+            lexed.push(SyntaxToken {
+                kind: SyntaxKind::Whitespace,
+                text: &input[end..span.start],
+                offset: end,
+            });
+        }
+        let kind = match token {
+            Some(Ok(Token::Word(_))) => SyntaxKind::Word,
+            Some(Ok(Token::Number(_))) => SyntaxKind::Number,
+            Some(Ok(Token::Comma)) => SyntaxKind::Comma,
+            Some(Ok(Token::ParenOpen)) => SyntaxKind::ParenOpen,
+            Some(Ok(Token::ParenClose)) => SyntaxKind::ParenClose,
+            Some(Err(_)) => SyntaxKind::Error,
+            None => break,
+        };
+        lexed.push(SyntaxToken {
+            kind,
+            text: tokens.slice(span),
+            offset: span.start,
+        });
+        end = span.end;
+    }
+    let rest = tokens.last_span().start.max(end);
+    if rest < input.len() {
+        lexed.push(SyntaxToken {
+            kind: SyntaxKind::Error,
+            text: &input[rest..],
+            offset: rest,
+        });
+    }
+    lexed
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    tokens: Vec<SyntaxToken<'a>>,
+    /// The index of the next token to be added to the tree.
+    next: usize,
+    /// How many lists are being read.
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    /// The kind of the next token, skipping whitespace if `skip_whitespace` is set.
+    fn peek_kind(&self, skip_whitespace: bool) -> Option<SyntaxKind> {
+        self.peek(skip_whitespace).map(SyntaxToken::kind)
+    }
+
+    fn peek(&self, skip_whitespace: bool) -> Option<&SyntaxToken<'a>> {
+        self.tokens[self.next..]
+            .iter()
+            .find(|token| !skip_whitespace || token.kind != SyntaxKind::Whitespace)
+    }
+
+    /// Whether the next token other than whitespace is a word matching one of `words`.
+    fn at_word(&self, words: &[&str]) -> bool {
+        match self.peek(true) {
+            Some(token) if token.kind == SyntaxKind::Word => words
+                .iter()
+                .any(|word| word.eq_ignore_ascii_case(token.text)),
+            _ => false,
+        }
+    }
+
+    fn bump(&mut self, children: &mut Vec<SyntaxElement<'a>>) {
+        children.push(SyntaxElement::Token(self.tokens[self.next]));
+        self.next += 1;
+    }
+
+    /// Adds the whitespace, if any, and then the token following it.
+    fn bump_after_whitespace(&mut self, children: &mut Vec<SyntaxElement<'a>>) {
+        if self.peek_kind(false) == Some(SyntaxKind::Whitespace) {
+            self.bump(children);
+        }
+        self.bump(children);
+    }
+
+    fn node(&self, kind: NodeKind, children: Vec<SyntaxElement<'a>>) -> SyntaxElement<'a> {
+        let start = children.first().map_or(0, |child| child.span().start);
+        let end = children.last().map_or(start, |child| child.span().end);
+        SyntaxElement::Node(SyntaxNode {
+            kind,
+            children,
+            input: self.input,
+            span: Span { start, end },
+        })
+    }
+
+    /// Reads a geometry starting at the type keyword that is the next token.
+    fn geometry(&mut self) -> SyntaxElement<'a> {
+        let mut children = Vec::new();
+        self.bump(&mut children);
+        if self.at_word(&["Z", "M", "ZM"]) {
+            self.bump_after_whitespace(&mut children);
+        }
+        if self.at_word(&["EMPTY"]) {
+            self.bump_after_whitespace(&mut children);
+        } else if self.peek_kind(true) == Some(SyntaxKind::ParenOpen) {
+            if self.peek_kind(false) == Some(SyntaxKind::Whitespace) {
+                self.bump(&mut children);
+            }
+            children.push(self.list());
+        }
+        self.node(NodeKind::Geometry, children)
+    }
+
+    /// Reads a list starting at the open paren that is the next token, up to its matching
+    /// close paren or the end of the input.
+    fn list(&mut self) -> SyntaxElement<'a> {
+        if self.depth == MAX_LIST_DEPTH {
+            return self.too_deep();
+        }
+        self.depth += 1;
+        let mut children = Vec::new();
+        self.bump(&mut children);
+        loop {
+            if self.peek_kind(false) == Some(SyntaxKind::Whitespace) {
+                self.bump(&mut children);
+            }
+            match self.peek_kind(false) {
+                None => break,
+                Some(SyntaxKind::ParenClose) => {
+                    self.bump(&mut children);
+                    break;
+                }
+                Some(SyntaxKind::ParenOpen) => children.push(self.list()),
+                Some(SyntaxKind::Number) => children.push(self.coord()),
+                Some(SyntaxKind::Word) if !self.at_word(&["EMPTY"]) => {
+                    children.push(self.geometry())
+                }
+                Some(_) => self.bump(&mut children),
+            }
+        }
+        self.depth -= 1;
+        self.node(NodeKind::List, children)
+    }
+
+    /// Reads a list nested too deep, starting at the open paren that is the next token, up to
+    /// its matching close paren or the end of the input, as a single error token.
+    fn too_deep(&mut self) -> SyntaxElement<'a> {
+        let start = self.tokens[self.next].offset;
+        let mut end = start;
+        let mut parens = 0;
+        while let Some(token) = self.tokens.get(self.next) {
+            match token.kind {
+                SyntaxKind::ParenOpen => parens += 1,
+                SyntaxKind::ParenClose => parens -= 1,
+                _ => (),
+            }
+            end = token.offset + token.text.len();
+            self.next += 1;
+            if parens == 0 {
+                break;
+            }
+        }
+        SyntaxElement::Token(SyntaxToken {
+            kind: SyntaxKind::Error,
+            text: &self.input[start..end],
+            offset: start,
+        })
+    }
+
+    /// Reads the numbers of a coordinate starting at the next token.
+    fn coord(&mut self) -> SyntaxElement<'a> {
+        let mut children = Vec::new();
+        self.bump(&mut children);
+        while self.peek_kind(true) == Some(SyntaxKind::Number) {
+            self.bump_after_whitespace(&mut children);
+        }
+        self.node(NodeKind::Coord, children)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cst, NodeKind, SyntaxElement, SyntaxKind, MAX_LIST_DEPTH};
+    use types::Point;
+    use {Error, Geometry, Limit, ParseOptions, Position, Wkt, DEFAULT_MAX_DEPTH};
+
+    #[test]
+    fn print_back() {
+        let inputs = [
+            "",
+            "  \n",
+            "POINT(1 2)",
+            "  point z\t( 1  2\n 3 )  ",
+            "MULTIPOLYGON (((0 0,1 0 , 0 1, 0 0)),EMPTY)\nLINESTRING EMPTY",
+            "GEOMETRYCOLLECTION ( POINT (1 2) ,LINESTRING(3 4,5 6) )",
+            "POINT (1 2))(; 1e x--1 é",
+            "POINT (1 2",
+            "POINT (1 2)\0 trailing",
+        ];
+        for input in inputs.iter() {
+            let cst = Cst::parse(input);
+            assert_eq!(*input, cst.to_string());
+        }
+    }
+
+    #[test]
+    fn cst_structure() {
+        let cst = Cst::parse(" POINT Z (1 2\t3) ");
+        let children = cst.children();
+        assert_eq!(3, children.len());
+        let point = match children[1] {
+            SyntaxElement::Node(ref node) => node,
+            _ => panic!("expected a node, found {:?}", children[1]),
+        };
+        assert_eq!(NodeKind::Geometry, point.kind());
+        assert_eq!("POINT Z (1 2\t3)", point.text());
+        let keywords: Vec<&str> = point.tokens().map(|token| token.text()).collect();
+        assert_eq!(vec!["POINT", "Z"], keywords);
+
+        let list = point.nodes().next().unwrap();
+        assert_eq!(NodeKind::List, list.kind());
+        let coord = list.nodes().next().unwrap();
+        assert_eq!(NodeKind::Coord, coord.kind());
+        assert_eq!(10, coord.span().start);
+        let kinds: Vec<SyntaxKind> = coord
+            .children()
+            .iter()
+            .map(|child| match *child {
+                SyntaxElement::Token(ref token) => token.kind(),
+                SyntaxElement::Node(_) => panic!("expected a token"),
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                SyntaxKind::Number,
+                SyntaxKind::Whitespace,
+                SyntaxKind::Number,
+                SyntaxKind::Whitespace,
+                SyntaxKind::Number,
+            ],
+            kinds
+        );
+    }
+
+    #[test]
+    fn lower_cst() {
+        let input = "POINT (1 2)\n  LINESTRING ( 3 4 , 5 6 )";
+        let cst = Cst::parse(input);
+        assert_eq!(2, cst.geometries().count());
+        let wkt = cst.to_wkt::<f64>(&ParseOptions::default()).ok().unwrap();
+        assert_eq!("POINT (1 2)\nLINESTRING (3 4, 5 6)", wkt.to_string());
+
+        let point = cst.geometries().next().unwrap();
+        match point.to_geometry::<f64>(&ParseOptions::default()) {
+            Ok(Geometry::Point(Point(Some(coord)))) => assert_eq!((1.0, 2.0), (coord.x, coord.y)),
+            other => panic!("expected a point, found {:?}", other),
+        }
+
+        let cst = Cst::parse("POINT (1 2)\n  POINT (3 1x)");
+        match cst.to_wkt::<f64>(&ParseOptions::default()) {
+            Err(Error::InvalidNumber { position, .. }) => assert_eq!(
+                Position {
+                    offset: 23,
+                    line: 2,
+                    column: 12,
+                },
+                position
+            ),
+            other => panic!("expected an invalid number, found {:?}", other),
+        }
+
+        let cst = Cst::parse("POINT (1 2) , POINT (3 4)");
+        match cst.to_wkt::<f64>(&ParseOptions::default()) {
+            Err(Error::UnexpectedToken { position, .. }) => assert_eq!(12, position.offset),
+            other => panic!("expected an unexpected token, found {:?}", other),
+        }
+
+        assert_eq!(
+            Wkt::<f64>::new(),
+            Cst::parse(" ")
+                .to_wkt(&ParseOptions::default())
+                .ok()
+                .unwrap()
+        );
+
+        // The caller's limits apply.
+        let options = ParseOptions {
+            max_coords: Some(2),
+            ..Default::default()
+        };
+        let cst = Cst::parse("POINT (1 2) LINESTRING (1 2, 3 4)");
+        let line = cst.geometries().nth(1).unwrap();
+        line.to_geometry::<f64>(&options).ok().unwrap();
+        match cst.to_wkt::<f64>(&options) {
+            Err(Error::LimitExceeded {
+                limit: Limit::Coords,
+                position,
+                ..
+            }) => assert_eq!(29, position.offset),
+            other => panic!("expected the coordinate limit, found {:?}", other),
+        }
+        let options = ParseOptions {
+            max_coords: Some(1),
+            ..Default::default()
+        };
+        line.to_geometry::<f64>(&options).err().unwrap();
+    }
+
+    #[test]
+    fn parse_limits() {
+        let inputs = [
+            "GEOMETRYCOLLECTION (".repeat(100_000),
+            format!("POINT {}", "(".repeat(200_000)),
+            format!(
+                "POINT {}1 2{}, 3 4",
+                "(".repeat(200_000),
+                ")".repeat(200_000)
+            ),
+        ];
+        for input in inputs.iter() {
+            let cst = Cst::parse(input);
+            assert_eq!(*input, cst.to_string());
+            cst.to_wkt::<f64>(&ParseOptions::default()).err().unwrap();
+        }
+        match Cst::parse(&inputs[0]).to_wkt::<f64>(&ParseOptions::default()) {
+            Err(Error::LimitExceeded {
+                limit: Limit::Depth,
+                ..
+            }) => (),
+            other => panic!("expected the depth limit, found {:?}", other),
+        }
+
+        // Anything the parser accepts by default is nested shallow enough.
+        let input = format!(
+            "{}MULTIPOLYGON (((0 0))){}",
+            "GEOMETRYCOLLECTION (".repeat(DEFAULT_MAX_DEPTH - 1),
+            ")".repeat(DEFAULT_MAX_DEPTH - 1)
+        );
+        Cst::parse(&input)
+            .to_wkt::<f64>(&ParseOptions::default())
+            .ok()
+            .unwrap();
+
+        // The list nested too deep ends at its matching close paren.
+        let input = format!("POINT {}1{} 2", "(".repeat(200), ")".repeat(200));
+        let cst = Cst::parse(&input);
+        let mut node = cst.geometries().next().unwrap();
+        let mut depth = 0;
+        while let Some(list) = node.nodes().next() {
+            node = list;
+            depth += 1;
+        }
+        assert_eq!(MAX_LIST_DEPTH, depth);
+        let error = node.tokens().nth(1).unwrap();
+        assert_eq!(SyntaxKind::Error, error.kind());
+        assert_eq!(
+            format!("{}1{}", "(".repeat(70), ")".repeat(70)),
+            error.text()
+        );
+    }
+}
@@ -0,0 +1,366 @@
+// Copyright 2015 The GeoRust Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::error;
+use std::fmt;
+use std::io;
+
+use tokenizer::Token;
+use types::Dimension;
+use GeometryType;
+use Limit;
+
+/// A location in the WKT input.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Position {
+    /// Byte offset from the start of the input.
+    pub offset: usize,
+    /// One-based line number.
+    pub line: usize,
+    /// One-based column, counted in characters.
+    pub column: usize,
+}
+
+impl Position {
+    /// Computes the line and column of a byte offset into `text`.
+    pub(crate) fn from_offset(text: &str, offset: usize) -> Self {
+        let before = &text[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        Position {
+            offset,
+            line: before.bytes().filter(|&b| b == b'\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+
+    /// Translates a position within a fragment of a larger input to one within the whole
+    /// input, given the position `base` where the fragment starts.
+    pub(crate) fn within(self, base: Position) -> Self {
+        Position {
+            offset: base.offset + self.offset,
+            line: base.line + self.line - 1,
+            column: if self.line == 1 {
+                base.column + self.column - 1
+            } else {
+                self.column
+            },
+        }
+    }
+
+    /// The position just past `text`, which starts at `self`.
+    pub(crate) fn after(self, text: &str) -> Self {
+        Position::from_offset(text, text.len()).within(self)
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+/// An error encountered while parsing WKT.
+///
+/// Wherever a token was found in place of the expected one it is included as `found`, with
+/// `None` meaning the input ended early.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+    UnexpectedToken {
+        expected: &'static str,
+        found: Option<String>,
+        position: Position,
+    },
+    MissingOpenParen {
+        found: Option<String>,
+        position: Position,
+    },
+    MissingCloseParen {
+        found: Option<String>,
+        position: Position,
+    },
+    UnknownGeometryType {
+        found: String,
+        position: Position,
+    },
+    /// Text that starts like a number but is not one, such as `1-2`.
+    InvalidNumber {
+        found: String,
+        position: Position,
+    },
+    /// A coordinate had a different number of values than its geometry's dimension requires.
+    CoordinateArity {
+        expected: usize,
+        found: usize,
+        position: Position,
+    },
+    /// A geometry declared a dimension conflicting with the one of its enclosing collection.
+    DimensionMismatch {
+        expected: Dimension,
+        found: Dimension,
+        position: Position,
+    },
+    /// A geometry of one type was found where another was required, as when parsing a
+    /// `Polygon` from `POINT (1 2)`.
+    TypeMismatch {
+        expected: GeometryType,
+        found: GeometryType,
+        position: Position,
+    },
+    /// The input went over one of the limits set in `ParseOptions`, with `max` the value of
+    /// that limit.
+    LimitExceeded {
+        limit: Limit,
+        max: usize,
+        position: Position,
+    },
+    /// Reading the input failed, with `kind` and `message` taken from the `io::Error`.
+    Io {
+        kind: io::ErrorKind,
+        message: String,
+        position: Position,
+    },
+}
+
+impl Error {
+    pub(crate) fn unexpected(
+        expected: &'static str,
+        found: Option<Token>,
+        position: Position,
+    ) -> Self {
+        Error::UnexpectedToken {
+            expected,
+            found: found.map(|token| token.to_string()),
+            position,
+        }
+    }
+
+    /// Where in the input the error occurred.
+    pub fn position(&self) -> Position {
+        match *self {
+            Error::UnexpectedToken { position, .. }
+            | Error::MissingOpenParen { position, .. }
+            | Error::MissingCloseParen { position, .. }
+            | Error::UnknownGeometryType { position, .. }
+            | Error::InvalidNumber { position, .. }
+            | Error::CoordinateArity { position, .. }
+            | Error::DimensionMismatch { position, .. }
+            | Error::TypeMismatch { position, .. }
+            | Error::LimitExceeded { position, .. }
+            | Error::Io { position, .. } => position,
+        }
+    }
+
+    pub(crate) fn position_mut(&mut self) -> &mut Position {
+        match *self {
+            Error::UnexpectedToken {
+                ref mut position, ..
+            }
+            | Error::MissingOpenParen {
+                ref mut position, ..
+            }
+            | Error::MissingCloseParen {
+                ref mut position, ..
+            }
+            | Error::UnknownGeometryType {
+                ref mut position, ..
+            }
+            | Error::InvalidNumber {
+                ref mut position, ..
+            }
+            | Error::CoordinateArity {
+                ref mut position, ..
+            }
+            | Error::DimensionMismatch {
+                ref mut position, ..
+            }
+            | Error::TypeMismatch {
+                ref mut position, ..
+            }
+            | Error::LimitExceeded {
+                ref mut position, ..
+            }
+            | Error::Io {
+                ref mut position, ..
+            } => position,
+        }
+    }
+
+    pub(crate) fn io(err: &io::Error, position: Position) -> Self {
+        Error::Io {
+            kind: err.kind(),
+            message: err.to_string(),
+            position,
+        }
+    }
+}
+
+struct Found<'a>(&'a Option<String>);
+
+impl<'a> fmt::Display for Found<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self.0 {
+            Some(ref token) => write!(f, "`{}`", token),
+            None => f.write_str("end of input"),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::UnexpectedToken {
+                expected,
+                ref found,
+                ..
+            } => write!(f, "expected {}, found {}", expected, Found(found))?,
+            Error::MissingOpenParen { ref found, .. } => {
+                write!(f, "expected `(` or EMPTY, found {}", Found(found))?
+            }
+            Error::MissingCloseParen { ref found, .. } => {
+                write!(f, "expected `)`, found {}", Found(found))?
+            }
+            Error::UnknownGeometryType { ref found, .. } => {
+                write!(f, "unknown geometry type `{}`", found)?
+            }
+            Error::InvalidNumber { ref found, .. } => write!(f, "invalid number `{}`", found)?,
+            Error::CoordinateArity {
+                expected, found, ..
+            } => write!(
+                f,
+                "expected a coordinate with {} values, found {}",
+                expected, found
+            )?,
+            Error::DimensionMismatch {
+                expected, found, ..
+            } => write!(
+                f,
+                "expected a geometry of dimension {:?}, found {:?}",
+                expected, found
+            )?,
+            Error::TypeMismatch {
+                expected, found, ..
+            } => write!(f, "{}", TypeMismatchError { expected, found })?,
+            Error::LimitExceeded { limit, max, .. } => {
+                let what = match limit {
+                    Limit::Depth => "nesting depth",
+                    Limit::Coords => "number of coordinates",
+                    Limit::InputLen => "input length",
+                };
+                write!(f, "{} exceeds the limit of {}", what, max)?
+            }
+            Error::Io { ref message, .. } => write!(f, "{}", message)?,
+        }
+        write!(f, " at {}", self.position())
+    }
+}
+
+impl error::Error for Error {}
+
+/// The error converting a `Geometry` to one of the geometry types fails with when it holds
+/// another type, as when converting `POINT (1 2)` to a `Polygon`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TypeMismatchError {
+    pub expected: GeometryType,
+    pub found: GeometryType,
+}
+
+impl fmt::Display for TypeMismatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "expected a {} geometry, found a {} geometry",
+            self.expected.keyword(),
+            self.found.keyword()
+        )
+    }
+}
+
+impl error::Error for TypeMismatchError {}
+
+#[cfg(test)]
+mod tests {
+    use super::{Error, Position};
+    use Wkt;
+
+    #[test]
+    fn error_positions() {
+        let err = Wkt::from_str("POINT (1 2").err().unwrap();
+        assert_eq!(
+            err,
+            Error::MissingCloseParen {
+                found: None,
+                position: Position {
+                    offset: 10,
+                    line: 1,
+                    column: 11,
+                },
+            }
+        );
+
+        let err = Wkt::from_str("LINESTRING (1 2,\n  3 4,\n  5 x)")
+            .err()
+            .unwrap();
+        assert_eq!(
+            err,
+            Error::CoordinateArity {
+                expected: 2,
+                found: 1,
+                position: Position {
+                    offset: 26,
+                    line: 3,
+                    column: 3,
+                },
+            }
+        );
+        assert_eq!(
+            "expected a coordinate with 2 values, found 1 at line 3, column 3",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn invalid_number() {
+        let err = Wkt::from_str("POINT (1 --2)").err().unwrap();
+        assert_eq!(
+            err,
+            Error::InvalidNumber {
+                found: "--2".to_string(),
+                position: Position {
+                    offset: 9,
+                    line: 1,
+                    column: 10,
+                },
+            }
+        );
+        Wkt::from_str("POINT (1-2 3)").err().unwrap();
+    }
+
+    #[test]
+    fn unknown_geometry_type() {
+        let err = Wkt::from_str("GEOMETRYCOLLECTION (POINT (1 2), CIRCLE Z (1 2 3))")
+            .err()
+            .unwrap();
+        assert_eq!(
+            err,
+            Error::UnknownGeometryType {
+                found: "CIRCLE".to_string(),
+                position: Position {
+                    offset: 33,
+                    line: 1,
+                    column: 34,
+                },
+            }
+        );
+    }
+}
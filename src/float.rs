@@ -0,0 +1,264 @@
+// Copyright 2015 The GeoRust Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#[cfg(feature = "ryu")]
+extern crate ryu;
+
+use std::fmt;
+
+/// A scalar type that coordinate values can be stored as.
+///
+/// Implemented for `f64`, the default everywhere, `f32`, and `Lossless`.
+pub trait WktFloat: Clone + Default + fmt::Debug + fmt::Display + PartialEq {
+    /// Converts an `f64`, such as a coordinate of a `geo` type.
+    fn from_f64(value: f64) -> Self;
+
+    /// Converts a number read by the parser, where `text` is the number as it appeared in the
+    /// input and `value` its `f64` value.
+    fn from_number(value: f64, text: &str) -> Self {
+        let _ = text;
+        Self::from_f64(value)
+    }
+
+    fn is_finite(&self) -> bool;
+
+    fn is_nan(&self) -> bool;
+
+    /// Writes the value for `NumberFormat::Ryu`, which is with `{}` unless overridden.
+    ///
+    /// `f64` and `f32` values are written with the Ryu algorithm, which is faster than `{}` and
+    /// also gives the shortest text that parses back to exactly the same value. The text can
+    /// differ from that of `{}` though: very large and very small values get an exponent, as
+    /// in `1e-7` rather than `0.0000001`, and the last digit sometimes differs by one.
+    #[cfg(feature = "ryu")]
+    fn write_ryu<W: fmt::Write>(&self, sink: &mut W) -> fmt::Result {
+        write!(sink, "{}", self)
+    }
+}
+
+/// Writes a finite `value` with Ryu, leaving out the `.0` it ends whole numbers with, and
+/// others with `{}`.
+#[cfg(feature = "ryu")]
+fn format_ryu<F, W>(value: F, finite: bool, sink: &mut W) -> fmt::Result
+where
+    F: ryu::Float + fmt::Display,
+    W: fmt::Write,
+{
+    if !finite {
+        return write!(sink, "{}", value);
+    }
+    let mut buffer = ryu::Buffer::new();
+    let text = buffer.format_finite(value);
+    sink.write_str(text.strip_suffix(".0").unwrap_or(text))
+}
+
+impl WktFloat for f64 {
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+
+    fn is_finite(&self) -> bool {
+        f64::is_finite(*self)
+    }
+
+    fn is_nan(&self) -> bool {
+        f64::is_nan(*self)
+    }
+
+    #[cfg(feature = "ryu")]
+    fn write_ryu<W: fmt::Write>(&self, sink: &mut W) -> fmt::Result {
+        format_ryu(*self, f64::is_finite(*self), sink)
+    }
+}
+
+impl WktFloat for f32 {
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+
+    /// Parses `text` directly, since rounding through `f64` is not always correct.
+    fn from_number(value: f64, text: &str) -> Self {
+        text.parse().unwrap_or(value as f32)
+    }
+
+    fn is_finite(&self) -> bool {
+        f32::is_finite(*self)
+    }
+
+    fn is_nan(&self) -> bool {
+        f32::is_nan(*self)
+    }
+
+    #[cfg(feature = "ryu")]
+    fn write_ryu<W: fmt::Write>(&self, sink: &mut W) -> fmt::Result {
+        format_ryu(*self, f32::is_finite(*self), sink)
+    }
+}
+
+/// A coordinate value that keeps the text it was parsed from, so that writing it gives back
+/// exactly that text, such as `0.1000000000000000055` or `2.50`.
+///
+/// Values that were not parsed, such as those converted from `f64`, are written like an `f64`.
+/// Only the numbers are kept as they were: keywords and whitespace are written the way the
+/// writer always writes them. Keeping all of the text is what a `cst::Cst` is for.
+///
+/// Values compare equal when their `f64` values do, whatever their text, so `2.5` equals
+/// `2.50`.
+#[derive(Clone, Debug, Default)]
+pub struct Lossless {
+    value: f64,
+    text: Option<Box<str>>,
+}
+
+impl PartialEq for Lossless {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl Lossless {
+    /// The value as an `f64`.
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    /// The text the value was parsed from, if it was parsed.
+    pub fn text(&self) -> Option<&str> {
+        self.text.as_deref()
+    }
+}
+
+impl From<f64> for Lossless {
+    fn from(value: f64) -> Self {
+        Lossless { value, text: None }
+    }
+}
+
+/// Writes the text the value was parsed from, unless a precision is given, as in `{:.2}`.
+impl fmt::Display for Lossless {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (f.precision(), self.text.as_deref()) {
+            (Some(precision), _) => write!(f, "{:.*}", precision, self.value),
+            (None, Some(text)) => f.write_str(text),
+            (None, None) => write!(f, "{}", self.value),
+        }
+    }
+}
+
+impl WktFloat for Lossless {
+    fn from_f64(value: f64) -> Self {
+        Lossless::from(value)
+    }
+
+    fn from_number(value: f64, text: &str) -> Self {
+        Lossless {
+            value,
+            text: Some(text.into()),
+        }
+    }
+
+    fn is_finite(&self) -> bool {
+        self.value.is_finite()
+    }
+
+    fn is_nan(&self) -> bool {
+        self.value.is_nan()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Lossless;
+    use std::str::FromStr;
+    use types::{Coord, LineString, Point};
+    use {Geometry, Wkt};
+    #[cfg(feature = "ryu")]
+    use {NumberFormat, WriteWkt, WriterOptions};
+
+    #[test]
+    fn lossless_numbers() {
+        let input = "LINESTRING (0.1000000000000000055 2.50, -0 1E+3, 3e-1 .5)";
+        let wkt = Wkt::<Lossless>::from_str(input).ok().unwrap();
+        assert_eq!(input, wkt.to_string());
+
+        let coords = match wkt.items[0] {
+            Geometry::LineString(ref line) => &line.0,
+            _ => unreachable!(),
+        };
+        assert_eq!(0.1, coords[0].x.value());
+        assert_eq!(Some("2.50"), coords[0].y.text());
+        assert_eq!(1000.0, coords[1].y.value());
+
+        assert_eq!("1.5", Lossless::from(1.5).to_string());
+        assert_eq!(None, Lossless::from(1.5).text());
+
+        // Equality ignores the text.
+        let point = Wkt::<Lossless>::from_str("POINT (2.50 1E+3)").ok().unwrap();
+        assert_eq!(
+            Wkt::<Lossless>::from_str("POINT (2.5 1000)").ok(),
+            Some(point)
+        );
+        assert_eq!(Lossless::from(2.5), coords[0].y);
+    }
+
+    #[test]
+    fn shortest_round_trip() {
+        let mut values = vec![
+            0.1,
+            1.0 / 3.0,
+            -0.0,
+            1e-7,
+            1e300,
+            5e-324,
+            f64::MAX,
+            f64::MIN_POSITIVE,
+            123456.789,
+        ];
+        let mut bits = 0x1234_5678_9abc_def0u64;
+        while values.len() < 1000 {
+            bits = bits
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            let value = f64::from_bits(bits);
+            if value.is_finite() {
+                values.push(value);
+            }
+        }
+        let coord = |x, y| Coord {
+            x,
+            y,
+            z: None,
+            m: None,
+        };
+        let line = LineString(values.iter().map(|&x| coord(x, -x)).collect());
+        let round_trip = |text: &str| {
+            let parsed = LineString::<f64>::from_str(text).ok().unwrap();
+            for (coord, parsed) in line.0.iter().zip(parsed.0.iter()) {
+                assert_eq!(coord.x.to_bits(), parsed.x.to_bits());
+                assert_eq!(coord.y.to_bits(), parsed.y.to_bits());
+            }
+        };
+        round_trip(&line.to_string());
+        #[cfg(feature = "ryu")]
+        {
+            let options = WriterOptions {
+                number_format: NumberFormat::Ryu,
+                ..Default::default()
+            };
+            round_trip(&line.with_options(&options).to_string());
+        }
+
+        assert_eq!("POINT (1 -0)", Point(Some(coord(1.0, -0.0))).to_string());
+    }
+}
@@ -12,10 +12,20 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::ascii::AsciiExt;
 use std::default::Default;
+use std::fmt;
+use std::str::FromStr;
 
-use tokenizer::{PeekableTokens, Token, Tokens};
+pub use error::{Error, Position, TypeMismatchError};
+pub use float::{Lossless, WktFloat};
+pub use options::{Limit, NonFinite, ParseOptions, DEFAULT_MAX_DEPTH};
+pub use parser::{Parsed, Parser, Processed};
+use processor::GeometryBuilder;
+pub use processor::{Processor, Spans};
+pub use reader::{Geometries, Separator, WktReader};
+pub use tokenizer::Span;
+use tokenizer::{PeekableTokens, Token};
+use types::Dimension;
 use types::GeometryCollection;
 use types::LineString;
 use types::MultiLineString;
@@ -23,8 +33,18 @@ use types::MultiPoint;
 use types::MultiPolygon;
 use types::Point;
 use types::Polygon;
+use writer::write_fmt_with;
+pub use writer::{Formatted, KeywordCase, NumberFormat, WktWriter, WriteWkt, WriterOptions};
 
-mod tokenizer;
+pub mod cst;
+mod error;
+mod float;
+mod options;
+mod parser;
+mod processor;
+mod reader;
+pub mod tokenizer;
+mod writer;
 
 #[cfg(feature = "geo")]
 mod towkt;
@@ -32,135 +52,390 @@ mod towkt;
 pub mod types;
 
 #[cfg(feature = "geo")]
-pub use towkt::ToWkt;
-
-pub enum Geometry {
-    Point(Point),
-    LineString(LineString),
-    Polygon(Polygon),
-    MultiPoint(MultiPoint),
-    MultiLineString(MultiLineString),
-    MultiPolygon(MultiPolygon),
-    GeometryCollection(GeometryCollection),
-}
-
-impl Geometry {
-    fn from_word_and_tokens(word: &str, tokens: &mut PeekableTokens) -> Result<Self, &'static str> {
-        match word {
-            "POINT" => {
-                let x = <Point as FromTokens>::from_tokens_with_parens(tokens);
-                x.map(|y| y.as_item())
-            }
-            "LINESTRING" => {
-                let x = <LineString as FromTokens>::from_tokens_with_parens(tokens);
-                x.map(|y| y.as_item())
-            }
-            "POLYGON" => {
-                let x = <Polygon as FromTokens>::from_tokens_with_parens(tokens);
-                x.map(|y| y.as_item())
-            }
-            "MULTIPOINT" => {
-                let x = <MultiPoint as FromTokens>::from_tokens_with_parens(tokens);
-                x.map(|y| y.as_item())
-            }
-            "MULTILINESTRING" => {
-                let x = <MultiLineString as FromTokens>::from_tokens_with_parens(tokens);
-                x.map(|y| y.as_item())
+pub use towkt::{ToWkt, ToWktExt};
+
+/// A geometry of any type.
+///
+/// The dimension of a geometry is not stored but taken from its coordinates, so one declared on
+/// a geometry without any of its own is lost: `POINT Z EMPTY` is written back as `POINT EMPTY`,
+/// and `GEOMETRYCOLLECTION Z (POINT (1 2 3))` as `GEOMETRYCOLLECTION (POINT Z (1 2 3))`. A
+/// `Processor` fed by the parser still receives the declared dimension, and a `cst::Cst` keeps
+/// the text as written.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Geometry<T: WktFloat = f64> {
+    Point(Point<T>),
+    LineString(LineString<T>),
+    Polygon(Polygon<T>),
+    MultiPoint(MultiPoint<T>),
+    MultiLineString(MultiLineString<T>),
+    MultiPolygon(MultiPolygon<T>),
+    GeometryCollection(GeometryCollection<T>),
+}
+
+impl<T: WktFloat> WriteWkt for Geometry<T> {
+    fn write_wkt(&self, f: &mut fmt::Formatter, options: &WriterOptions) -> fmt::Result {
+        write_fmt_with(f, options, |writer| self.process(writer))
+    }
+}
+
+impl<T: WktFloat> fmt::Display for Geometry<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write_wkt(f, &WriterOptions::default())
+    }
+}
+
+/// Parses a single geometry, failing if the input is empty or anything other than whitespace
+/// follows the geometry.
+impl<T: WktFloat> FromStr for Geometry<T> {
+    type Err = Error;
+
+    fn from_str(wkt_str: &str) -> Result<Self, Error> {
+        let mut builder = GeometryBuilder::new();
+        Parser::new().process(wkt_str, &mut builder)?;
+        builder
+            .finish()
+            .pop()
+            .ok_or_else(|| Error::UnexpectedToken {
+                expected: "a geometry type",
+                found: None,
+                position: Position::from_offset(wkt_str, wkt_str.len()),
+            })
+    }
+}
+
+/// The kinds of geometry, as named by their WKT keywords.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GeometryType {
+    Point,
+    LineString,
+    Polygon,
+    MultiPoint,
+    MultiLineString,
+    MultiPolygon,
+    GeometryCollection,
+}
+
+impl GeometryType {
+    /// The keyword naming this type in WKT.
+    pub fn keyword(self) -> &'static str {
+        match self {
+            GeometryType::Point => "POINT",
+            GeometryType::LineString => "LINESTRING",
+            GeometryType::Polygon => "POLYGON",
+            GeometryType::MultiPoint => "MULTIPOINT",
+            GeometryType::MultiLineString => "MULTILINESTRING",
+            GeometryType::MultiPolygon => "MULTIPOLYGON",
+            GeometryType::GeometryCollection => "GEOMETRYCOLLECTION",
+        }
+    }
+
+    /// Looks up the geometry type keyword most recently read from `tokens`, along with the
+    /// dimension declared by a PostGIS style `Z`, `M` or `ZM` suffix, as in `POINTM`.
+    fn from_word(word: &str, tokens: &PeekableTokens) -> Result<(Self, Option<Dimension>), Error> {
+        const TYPES: [GeometryType; 7] = [
+            GeometryType::Point,
+            GeometryType::LineString,
+            GeometryType::Polygon,
+            GeometryType::MultiPoint,
+            GeometryType::MultiLineString,
+            GeometryType::MultiPolygon,
+            GeometryType::GeometryCollection,
+        ];
+        const SUFFIXES: [(&str, Option<Dimension>); 4] = [
+            ("", None),
+            ("Z", Some(Dimension::XYZ)),
+            ("M", Some(Dimension::XYM)),
+            ("ZM", Some(Dimension::XYZM)),
+        ];
+        for &(suffix, dim) in SUFFIXES.iter() {
+            if word.len() <= suffix.len() || !word.is_char_boundary(word.len() - suffix.len()) {
+                continue;
             }
-            "MULTIPOLYGON" => {
-                let x = <MultiPolygon as FromTokens>::from_tokens_with_parens(tokens);
-                x.map(|y| y.as_item())
+            let (keyword, word_suffix) = word.split_at(word.len() - suffix.len());
+            if !word_suffix.eq_ignore_ascii_case(suffix) {
+                continue;
             }
-            "GEOMETRYCOLLECTION" => {
-                let x = <GeometryCollection as FromTokens>::from_tokens_with_parens(tokens);
-                x.map(|y| y.as_item())
+            let found = TYPES
+                .iter()
+                .cloned()
+                .find(|geometry_type| geometry_type.keyword().eq_ignore_ascii_case(keyword));
+            if let Some(geometry_type) = found {
+                return Ok((geometry_type, dim));
             }
-            _ => Err("Invalid type encountered"),
+        }
+        Err(Error::UnknownGeometryType {
+            found: word.to_string(),
+            position: tokens.last_position(),
+        })
+    }
+}
+
+impl<T: WktFloat> Geometry<T> {
+    /// Reports the geometry to `processor` the way the parser reports a parsed one.
+    pub fn process<P: Processor<T>>(&self, processor: &mut P) {
+        match *self {
+            Geometry::Point(ref point) => point.process(processor),
+            Geometry::LineString(ref linestring) => linestring.process(processor),
+            Geometry::Polygon(ref polygon) => polygon.process(processor),
+            Geometry::MultiPoint(ref multipoint) => multipoint.process(processor),
+            Geometry::MultiLineString(ref multilinestring) => multilinestring.process(processor),
+            Geometry::MultiPolygon(ref multipolygon) => multipolygon.process(processor),
+            Geometry::GeometryCollection(ref collection) => collection.process(processor),
+        }
+    }
+
+    pub fn geometry_type(&self) -> GeometryType {
+        match *self {
+            Geometry::Point(_) => GeometryType::Point,
+            Geometry::LineString(_) => GeometryType::LineString,
+            Geometry::Polygon(_) => GeometryType::Polygon,
+            Geometry::MultiPoint(_) => GeometryType::MultiPoint,
+            Geometry::MultiLineString(_) => GeometryType::MultiLineString,
+            Geometry::MultiPolygon(_) => GeometryType::MultiPolygon,
+            Geometry::GeometryCollection(_) => GeometryType::GeometryCollection,
+        }
+    }
+
+    fn from_word_and_tokens(word: &str, tokens: &mut PeekableTokens) -> Result<Self, Error> {
+        let mut builder = GeometryBuilder::new();
+        process_word_and_tokens(word, tokens, &mut builder)?;
+        match builder.finish().pop() {
+            Some(geometry) => Ok(geometry),
+            None => unreachable!(),
+        }
+    }
+}
+
+/// Looks up the geometry type keyword `word` just read from `tokens`, along with the dimension
+/// declared by its suffix, or else by an ISO dimension keyword following it, as in `POINT M`.
+fn type_and_dim_from_word(
+    word: &str,
+    tokens: &mut PeekableTokens,
+) -> Result<(GeometryType, Option<Dimension>), Error> {
+    let (geometry_type, dim) = GeometryType::from_word(word, tokens)?;
+    let dim = match dim {
+        Some(dim) => Some(dim),
+        None => Dimension::from_tokens(tokens)?,
+    };
+    Ok((geometry_type, dim))
+}
+
+/// Parses the geometry whose type keyword `word` was just read from `tokens`, starting with its
+/// optional dimension keyword. Without one, the dimension is inferred from the first
+/// coordinate.
+fn process_word_and_tokens<T, P>(
+    word: &str,
+    tokens: &mut PeekableTokens,
+    processor: &mut P,
+) -> Result<(), Error>
+where
+    T: WktFloat,
+    P: Processor<T>,
+{
+    let start = tokens.last_span().start;
+    let (geometry_type, dim) = type_and_dim_from_word(word, tokens)?;
+    let dim = match dim {
+        Some(dim) => dim,
+        None => Dimension::infer(geometry_type, tokens),
+    };
+    process_type_dim_and_tokens(geometry_type, dim, start, tokens, processor)
+}
+
+/// Parses the body of a geometry of type `geometry_type` whose keyword starts at `start`,
+/// reporting it to `processor` between `geometry_begin` and `geometry_end`.
+fn process_type_dim_and_tokens<T, P>(
+    geometry_type: GeometryType,
+    dim: Dimension,
+    start: usize,
+    tokens: &mut PeekableTokens,
+    processor: &mut P,
+) -> Result<(), Error>
+where
+    T: WktFloat,
+    P: Processor<T>,
+{
+    tokens.enter_geometry()?;
+    processor.geometry_begin(geometry_type, dim);
+    match geometry_type {
+        GeometryType::Point => {
+            <Point<T> as FromTokens<T>>::from_tokens_with_parens(tokens, dim, processor)?
+        }
+        GeometryType::LineString => {
+            <LineString<T> as FromTokens<T>>::from_tokens_with_parens(tokens, dim, processor)?
+        }
+        GeometryType::Polygon => {
+            <Polygon<T> as FromTokens<T>>::from_tokens_with_parens(tokens, dim, processor)?
+        }
+        GeometryType::MultiPoint => {
+            <MultiPoint<T> as FromTokens<T>>::from_tokens_with_parens(tokens, dim, processor)?
+        }
+        GeometryType::MultiLineString => {
+            <MultiLineString<T> as FromTokens<T>>::from_tokens_with_parens(tokens, dim, processor)?
+        }
+        GeometryType::MultiPolygon => {
+            <MultiPolygon<T> as FromTokens<T>>::from_tokens_with_parens(tokens, dim, processor)?
+        }
+        GeometryType::GeometryCollection => {
+            <GeometryCollection<T> as FromTokens<T>>::from_tokens_with_parens(
+                tokens, dim, processor,
+            )?
         }
     }
+    processor.geometry_end();
+    processor.span(tokens.span_from(start));
+    tokens.exit_geometry();
+    Ok(())
 }
-pub struct Wkt {
-    pub items: Vec<Geometry>,
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Wkt<T: WktFloat = f64> {
+    pub items: Vec<Geometry<T>>,
 }
 
-impl Wkt {
+impl<T: WktFloat> Wkt<T> {
     pub fn new() -> Self {
         Wkt { items: vec![] }
     }
 
-    pub fn add_item(&mut self, item: Geometry) {
+    pub fn add_item(&mut self, item: Geometry<T>) {
         self.items.push(item);
     }
 
-    pub fn from_str(wkt_str: &str) -> Result<Self, &'static str> {
-        let tokens = Tokens::from_str(wkt_str);
-        Wkt::from_tokens(tokens)
+    /// Reports each item to `processor` in turn, the way the parser reports parsed ones.
+    pub fn process<P: Processor<T>>(&self, processor: &mut P) {
+        for item in &self.items {
+            item.process(processor);
+        }
     }
+}
 
-    fn from_tokens(tokens: Tokens) -> Result<Self, &'static str> {
-        let mut wkt = Wkt::new();
-        let mut tokens = tokens.peekable();
-        let word = match tokens.next() {
-            Some(Token::Word(word)) => {
-                if !word.is_ascii() {
-                    return Err("Encountered non-ascii word");
-                }
-                word.to_ascii_uppercase()
-            }
-            None => return Ok(wkt),
-            _ => return Err("Invalid WKT format"),
-        };
-        match Geometry::from_word_and_tokens(&word, &mut tokens) {
-            Ok(item) => wkt.add_item(item),
-            Err(s) => return Err(s),
-        }
-        Ok(wkt)
+impl Wkt<f64> {
+    /// Parses a single geometry with coordinates stored as `f64`, failing if anything other
+    /// than whitespace follows it. Empty input gives no items. `Parser` has the other ways of
+    /// parsing.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(wkt_str: &str) -> Result<Self, Error> {
+        FromStr::from_str(wkt_str)
     }
 }
 
-trait FromTokens: Sized + Default {
-    fn from_tokens(tokens: &mut PeekableTokens) -> Result<Self, &'static str>;
+/// Parses a single geometry, failing if anything other than whitespace follows it. Empty input
+/// gives no items.
+impl<T: WktFloat> FromStr for Wkt<T> {
+    type Err = Error;
+
+    fn from_str(wkt_str: &str) -> Result<Self, Error> {
+        Parser::new().parse(wkt_str).map(|parsed| parsed.wkt)
+    }
+}
+
+/// Writes each item on its own line.
+impl<T: WktFloat> WriteWkt for Wkt<T> {
+    fn write_wkt(&self, f: &mut fmt::Formatter, options: &WriterOptions) -> fmt::Result {
+        write_fmt_with(f, options, |writer| self.process(writer))
+    }
+}
+
+impl<T: WktFloat> fmt::Display for Wkt<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write_wkt(f, &WriterOptions::default())
+    }
+}
 
-    fn from_tokens_with_parens(tokens: &mut PeekableTokens) -> Result<Self, &'static str> {
+/// Parsing of the body of one type of geometry, reporting what was read to a `Processor`.
+trait FromTokens<T: WktFloat> {
+    fn from_tokens<P: Processor<T>>(
+        tokens: &mut PeekableTokens,
+        dim: Dimension,
+        processor: &mut P,
+    ) -> Result<(), Error>;
+
+    fn from_tokens_with_parens<P: Processor<T>>(
+        tokens: &mut PeekableTokens,
+        dim: Dimension,
+        processor: &mut P,
+    ) -> Result<(), Error> {
+        let outer = tokens.paren_depth();
         match tokens.next() {
-            Some(Token::ParenOpen) => (),
-            Some(Token::Word(ref s)) if s.to_ascii_uppercase() == "EMPTY" => {
-                return Ok(Default::default())
+            Ok(Some(Token::ParenOpen)) => (),
+            Ok(Some(Token::Word(s))) if s.eq_ignore_ascii_case("EMPTY") => return Ok(()),
+            other => {
+                let err = match other {
+                    Err(err) => err,
+                    Ok(other) => Error::MissingOpenParen {
+                        found: other.map(|token| token.to_string()),
+                        position: tokens.last_position(),
+                    },
+                };
+                // Recover by treating the geometry as empty.
+                tokens.recover(err)?;
+                tokens.skip_to(outer);
+                return Ok(());
             }
-            _ => return Err("Missing open parenthesis for type"),
         };
-        let result = FromTokens::from_tokens(tokens);
+        let depth = outer + 1;
+        if let Err(err) = Self::from_tokens(tokens, dim, processor) {
+            // Recover by skipping the rest of the body, along with the paren closing it.
+            tokens.recover(err)?;
+            tokens.skip_past(depth);
+        }
+        if tokens.paren_depth() < depth {
+            // Already closed while recovering from an error.
+            return Ok(());
+        }
         match tokens.next() {
-            Some(Token::ParenClose) => (),
-            _ => return Err("Missing closing parenthesis for type"),
-        };
-        result
+            Ok(Some(Token::ParenClose)) => Ok(()),
+            other => {
+                let err = match other {
+                    Err(err) => err,
+                    Ok(other) => Error::MissingCloseParen {
+                        found: other.map(|token| token.to_string()),
+                        position: tokens.last_position(),
+                    },
+                };
+                tokens.recover(err)?;
+                tokens.skip_past(depth);
+                Ok(())
+            }
+        }
     }
 
-    fn comma_many<F>(f: F, tokens: &mut PeekableTokens) -> Result<Vec<Self>, &'static str>
+    fn comma_many<P, F>(
+        f: F,
+        tokens: &mut PeekableTokens,
+        dim: Dimension,
+        processor: &mut P,
+    ) -> Result<(), Error>
     where
-        F: Fn(&mut PeekableTokens) -> Result<Self, &'static str>,
+        F: Fn(&mut PeekableTokens, Dimension, &mut P) -> Result<(), Error>,
     {
-        let mut items = Vec::new();
-
-        let item = try!(f(tokens));
-        items.push(item);
-
-        while let Some(&Token::Comma) = tokens.peek() {
-            tokens.next(); // throw away comma
-
-            let item = try!(f(tokens));
-            items.push(item);
+        let depth = tokens.paren_depth();
+        loop {
+            if let Err(err) = f(tokens, dim, processor) {
+                // Recover by skipping the rest of the item.
+                tokens.recover(err)?;
+                tokens.skip_to(depth);
+            }
+            if tokens.paren_depth() < depth {
+                return Ok(());
+            }
+            match tokens.peek() {
+                Ok(Some(&Token::Comma)) => {
+                    tokens.next()?; // throw away comma
+                }
+                _ => return Ok(()),
+            }
         }
-
-        Ok(items)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use types::{MultiPolygon, Point};
-    use {Geometry, Wkt};
+    use std::convert::TryFrom;
+    use types::{MultiPolygon, Point, Polygon};
+    use DEFAULT_MAX_DEPTH;
+    use {Error, Geometry, GeometryType, Limit, ParseOptions, Parser, Position, Separator};
+    use {TypeMismatchError, Wkt};
 
     #[test]
     fn empty_string() {
@@ -184,4 +459,259 @@ mod tests {
             _ => unreachable!(),
         };
     }
+
+    #[test]
+    fn parse_limits() {
+        let nested = "GEOMETRYCOLLECTION (".repeat(100_000);
+        match Wkt::from_str(&nested) {
+            Err(Error::LimitExceeded {
+                limit: Limit::Depth,
+                max: DEFAULT_MAX_DEPTH,
+                ..
+            }) => (),
+            _ => unreachable!(),
+        };
+
+        let options = ParseOptions {
+            max_depth: Some(2),
+            max_coords: Some(3),
+            max_input_len: Some(64),
+            ..Default::default()
+        };
+        let parser = Parser::with_options(options.clone());
+        let parse = |wkt_str| parser.parse::<f64>(wkt_str);
+        parse("GEOMETRYCOLLECTION (MULTIPOINT (1 2, 3 4, 5 6))")
+            .ok()
+            .unwrap();
+        let err = parse("GEOMETRYCOLLECTION (GEOMETRYCOLLECTION (POINT EMPTY))")
+            .err()
+            .unwrap();
+        assert_eq!(40, err.position().offset);
+        let err = parse("GEOMETRYCOLLECTION (POINT (1 2), LINESTRING (3 4, 5 6, 7 8))")
+            .err()
+            .unwrap();
+        assert_eq!(55, err.position().offset);
+        parse(&format!("POINT ({:064})", 1)).err().unwrap();
+
+        // The coordinate limit applies to all geometries together.
+        let input = "MULTIPOINT (1 2, 3 4)\nMULTIPOINT (1 2, 3 4)";
+        let parser = parser.many(Separator::Newline);
+        let err = parser.parse::<f64>(input).err().unwrap();
+        assert_eq!(39, err.position().offset);
+        let mut geometries = parser.iter::<f64>(input);
+        geometries.next().unwrap().ok().unwrap();
+        geometries.next().unwrap().err().unwrap();
+    }
+
+    #[test]
+    fn parse_geometry() {
+        let geometry = "POINT(1 2)".parse::<Geometry>().ok().unwrap();
+        assert_eq!(GeometryType::Point, geometry.geometry_type());
+        assert_eq!("POINT (1 2)", geometry.to_string());
+        let point = Point::try_from(geometry.clone()).ok().unwrap();
+        assert_eq!(Geometry::Point(point), geometry);
+        "  ".parse::<Geometry>().err().unwrap();
+        "POINT (1 2) POINT (3 4)".parse::<Geometry>().err().unwrap();
+
+        let polygon = "POLYGON ((0 0, 1 0, 0 0))".parse::<Polygon>().ok().unwrap();
+        assert_eq!(1, polygon.0.len());
+        let err = "\n POINT (1 2)".parse::<Polygon>().err().unwrap();
+        assert_eq!(
+            Error::TypeMismatch {
+                expected: GeometryType::Polygon,
+                found: GeometryType::Point,
+                position: Position {
+                    offset: 2,
+                    line: 2,
+                    column: 2,
+                },
+            },
+            err
+        );
+        assert_eq!(
+            "expected a POLYGON geometry, found a POINT geometry at line 2, column 2",
+            err.to_string()
+        );
+        let err = MultiPolygon::<f64>::try_from(geometry).err().unwrap();
+        assert_eq!(
+            TypeMismatchError {
+                expected: GeometryType::MultiPolygon,
+                found: GeometryType::Point,
+            },
+            err
+        );
+        assert_eq!(
+            "expected a MULTIPOLYGON geometry, found a POINT geometry",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn recovering() {
+        let recover = |wkt_str: &str| {
+            let parsed = Parser::new()
+                .recovering()
+                .parse::<f64>(wkt_str)
+                .ok()
+                .unwrap();
+            (parsed.wkt, parsed.errors)
+        };
+        let (wkt, errors) = recover("LINESTRING (1 2, 3 x, 5 6, 7 --8, 9 1)");
+        assert_eq!("LINESTRING (1 2, 5 6, 9 1)", wkt.to_string());
+        assert_eq!(2, errors.len());
+        assert_eq!(17, errors[0].position().offset);
+        match errors[1] {
+            Error::InvalidNumber { ref found, .. } => assert_eq!("--8", found),
+            _ => unreachable!(),
+        };
+
+        let input = "MULTIPOLYGON (((0 0, 1 0, 0 0)), ((1 1, 2 2, 1 1)";
+        let (wkt, errors) = recover(input);
+        assert_eq!(
+            "MULTIPOLYGON (((0 0, 1 0, 0 0)), ((1 1, 2 2, 1 1)))",
+            wkt.to_string()
+        );
+        assert_eq!(2, errors.len());
+
+        let input = "GEOMETRYCOLLECTION (POINT (1 2 (3)), CIRCLE (1 2), POINT 3 4, POINT (5 6)";
+        let (wkt, errors) = recover(input);
+        assert_eq!(
+            "GEOMETRYCOLLECTION (POINT (1 2), POINT EMPTY, POINT (5 6))",
+            wkt.to_string()
+        );
+        let offsets = errors
+            .iter()
+            .map(|err| err.position().offset)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![31, 37, 57, 73], offsets);
+
+        let (wkt, errors) = recover("POINT (1 2) x");
+        assert_eq!(1, wkt.items.len());
+        assert_eq!(1, errors.len());
+
+        // An error in the body is reported once, not again as a missing paren after it.
+        for input in ["POINT (1e400 2)", "POINT ((1 2))", "POINT (1 x 2)"].iter() {
+            let (wkt, errors) = recover(input);
+            assert_eq!("POINT EMPTY", wkt.to_string());
+            assert_eq!(1, errors.len(), "{:?}", errors);
+        }
+        let (wkt, errors) = recover("MULTIPOINT ((1e400 2), (3 4)) LINESTRING");
+        assert_eq!("MULTIPOINT (EMPTY, (3 4))", wkt.to_string());
+        assert_eq!(2, errors.len(), "{:?}", errors);
+
+        let input = "MULTIPOINT (1 2, (3 4), EMPTY)";
+        let (wkt, errors) = recover(input);
+        assert!(errors.is_empty());
+        assert_eq!(Wkt::from_str(input).ok().unwrap(), wkt);
+    }
+
+    #[test]
+    fn trailing_input() {
+        let prefix = |wkt_str: &str| {
+            let parsed = Parser::new().prefix().parse::<f64>(wkt_str).ok().unwrap();
+            (parsed.wkt, parsed.consumed)
+        };
+        Wkt::from_str("POINT (1 2) garbage").err().unwrap();
+        Wkt::from_str("POINT (1 2))").err().unwrap();
+        Wkt::from_str("POINT (1 2) POINT (3 4)").err().unwrap();
+        Wkt::from_str("POINT (1 2) \n\t").ok().unwrap();
+        match Wkt::from_str("POINT (1 2)\0garbage") {
+            Err(Error::UnexpectedToken {
+                expected: "end of input",
+                position,
+                ..
+            }) => assert_eq!(11, position.offset),
+            other => panic!("expected trailing input, found {:?}", other),
+        }
+
+        let (wkt, consumed) = prefix(" POINT (1 2) garbage");
+        assert_eq!(1, wkt.items.len());
+        assert_eq!(12, consumed);
+
+        let (wkt, consumed) = prefix("POINT EMPTY;POINT (1 2)");
+        assert_eq!(1, wkt.items.len());
+        assert_eq!(11, consumed);
+
+        let (wkt, consumed) = prefix("  ");
+        assert_eq!(0, wkt.items.len());
+        assert_eq!(0, consumed);
+    }
+
+    #[test]
+    fn round_trip() {
+        let inputs = [
+            "POINT (1 2)",
+            "POINT EMPTY",
+            "POINT Z (1 2 3)",
+            "POINT ZM (1 2 3 4)",
+            "LINESTRING M (1 2 3, 4 5 6)",
+            "LINESTRING (1.5 2, -3 4e-7)",
+            "POLYGON ((0 0, 10 0, 10 10, 0 0), (1 1, 2 1, 2 2, 1 1))",
+            "POLYGON M (EMPTY, (0 0 1, 1 0 1, 0 0 1))",
+            "MULTIPOINT ((1 2), (3 4))",
+            "MULTILINESTRING ((1 2, 3 4), EMPTY)",
+            "MULTIPOLYGON (((0 0, 1 0, 1 1, 0 0)), ((5 5, 6 5, 6 6, 5 5)))",
+            "GEOMETRYCOLLECTION (POINT (1 2), GEOMETRYCOLLECTION EMPTY)",
+            "GEOMETRYCOLLECTION EMPTY",
+        ];
+        for input in inputs.iter() {
+            let wkt = Wkt::from_str(input).ok().unwrap();
+            let written = wkt.to_string();
+            assert_eq!(wkt, Wkt::from_str(&written).ok().unwrap());
+        }
+    }
+
+    #[test]
+    fn postgis_dimensions() {
+        let cases = [
+            ("POINTM(1 2 3)", "POINT M (1 2 3)"),
+            ("pointzm (1 2 3 4)", "POINT ZM (1 2 3 4)"),
+            (
+                "MULTIPOINTZM ((1 2 3 4), (5 6 7 8))",
+                "MULTIPOINT ZM ((1 2 3 4), (5 6 7 8))",
+            ),
+            // Declared dimensions without coordinates to carry them are not kept.
+            ("POINTZ EMPTY", "POINT EMPTY"),
+            ("POINT(1 2 3)", "POINT Z (1 2 3)"),
+            ("POINT (1 2 3 4)", "POINT ZM (1 2 3 4)"),
+            ("LINESTRING (1 2, 3 4)", "LINESTRING (1 2, 3 4)"),
+            (
+                "MULTIPOLYGON (EMPTY, ((0 0 1, 1 0 1, 0 0 1)))",
+                "MULTIPOLYGON Z (EMPTY, ((0 0 1, 1 0 1, 0 0 1)))",
+            ),
+            (
+                "GEOMETRYCOLLECTION (POINT (1 2 3), POINTM (1 2 3), POINT (1 2))",
+                "GEOMETRYCOLLECTION (POINT Z (1 2 3), POINT M (1 2 3), POINT (1 2))",
+            ),
+            (
+                "GEOMETRYCOLLECTION Z (POINT (1 2 3))",
+                "GEOMETRYCOLLECTION (POINT Z (1 2 3))",
+            ),
+        ];
+        for &(input, expected) in cases.iter() {
+            let wkt = Wkt::from_str(input).ok().unwrap();
+            assert_eq!(expected, wkt.to_string());
+        }
+
+        match Wkt::from_str("LINESTRING (1 2 3, 4 5)") {
+            Err(Error::CoordinateArity {
+                expected: 3,
+                found: 2,
+                position,
+            }) => assert_eq!(19, position.offset),
+            other => panic!("expected a coordinate arity error, found {:?}", other),
+        }
+        Wkt::from_str("MULTIPOINT ((1 2), (3 4 5))").err().unwrap();
+        Wkt::from_str("GEOMETRYCOLLECTION Z (POINT (1 2))")
+            .err()
+            .unwrap();
+        Wkt::from_str("GEOMETRYCOLLECTION Z (POINTM (1 2 3))")
+            .err()
+            .unwrap();
+        Wkt::from_str("POINTZ Z (1 2 3)").err().unwrap();
+        match Wkt::from_str("POINTX (1 2)") {
+            Err(Error::UnknownGeometryType { ref found, .. }) => assert_eq!("POINTX", found),
+            other => panic!("expected an unknown geometry type, found {:?}", other),
+        }
+    }
 }
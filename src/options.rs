@@ -0,0 +1,68 @@
+// Copyright 2015 The GeoRust Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/// Settings controlling how WKT is parsed.
+///
+/// The limits guard against untrusted input; going over one fails with
+/// `Error::LimitExceeded`. `None` means no limit.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseOptions {
+    /// How `NaN`, `Inf` and `Infinity` coordinate values are treated.
+    pub non_finite: NonFinite,
+    /// The maximum number of geometries nested inside each other, counting the outermost one.
+    /// Defaults to `DEFAULT_MAX_DEPTH`, since deeper nesting can overflow the stack.
+    pub max_depth: Option<usize>,
+    /// The maximum number of coordinates in the whole input, including those of every member
+    /// and of every geometry when parsing several, as with `Parser::many`. `WktReader`
+    /// applies it to each geometry instead, since it only holds one at a time.
+    pub max_coords: Option<usize>,
+    /// The maximum length of the input in bytes. `WktReader` applies it to the text of each
+    /// geometry instead.
+    pub max_input_len: Option<usize>,
+}
+
+/// The default `ParseOptions::max_depth`.
+pub const DEFAULT_MAX_DEPTH: usize = 128;
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            non_finite: NonFinite::default(),
+            max_depth: Some(DEFAULT_MAX_DEPTH),
+            max_coords: None,
+            max_input_len: None,
+        }
+    }
+}
+
+/// The limits that can be set in `ParseOptions`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Limit {
+    Depth,
+    Coords,
+    InputLen,
+}
+
+/// Policies for non-finite coordinate values.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NonFinite {
+    /// Fail with `Error::InvalidNumber`, as PostGIS does.
+    #[default]
+    Reject,
+    /// Keep the values as they are, as GEOS does.
+    Accept,
+    /// Like `Accept`, except that a point whose values are all `NaN` is read as `EMPTY`. This
+    /// is how PostGIS and GEOS represent empty points in WKB.
+    Empty,
+}
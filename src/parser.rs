@@ -0,0 +1,259 @@
+// Copyright 2015 The GeoRust Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use processor::{GeometryBuilder, SpanBuilder};
+use reader::next_keyword;
+use tokenizer::{PeekableTokens, Tokens};
+use {process_word_and_tokens, Error, Geometries, ParseOptions, Processor, Separator, Spans};
+use {Wkt, WktFloat};
+
+/// Parses WKT with the given settings. By default it reads a single geometry, failing if
+/// anything other than whitespace follows it, with the default `ParseOptions`.
+///
+/// ```
+/// use wkt::{Parser, Separator};
+///
+/// let parsed = Parser::new()
+///     .many(Separator::Char(';'))
+///     .spans()
+///     .parse::<f32>("POINT (1 2); LINESTRING (1 2, 3 4)")
+///     .unwrap();
+/// assert_eq!(2, parsed.wkt.items.len());
+/// assert_eq!(2, parsed.spans.len());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Parser {
+    options: ParseOptions,
+    separator: Option<Separator>,
+    spans: bool,
+    recovering: bool,
+    prefix: bool,
+}
+
+/// The result of `Parser::parse`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Parsed<T: WktFloat = f64> {
+    /// The geometries parsed, in order.
+    pub wkt: Wkt<T>,
+    /// The spans of the input that each item and its parts were parsed from, if requested with
+    /// `Parser::spans`. Empty otherwise.
+    pub spans: Vec<Spans>,
+    /// The number of bytes up to the end of the last geometry.
+    pub consumed: usize,
+    /// The errors recovered from, if requested with `Parser::recovering`. Empty otherwise,
+    /// since the first error then fails the parse.
+    pub errors: Vec<Error>,
+}
+
+/// The result of `Parser::process`, besides what was reported to the processor.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Processed {
+    /// The number of bytes up to the end of the last geometry.
+    pub consumed: usize,
+    /// The errors recovered from, if requested with `Parser::recovering`. Empty otherwise,
+    /// since the first error then fails the parse.
+    pub errors: Vec<Error>,
+}
+
+impl Parser {
+    pub fn new() -> Self {
+        Parser::default()
+    }
+
+    /// Like `new`, but with explicit limits and handling of non-finite values.
+    pub fn with_options(options: ParseOptions) -> Self {
+        Parser {
+            options,
+            ..Parser::default()
+        }
+    }
+
+    /// Reads every geometry of the input, with `separator` between consecutive ones, instead
+    /// of a single one.
+    pub fn many(mut self, separator: Separator) -> Self {
+        self.separator = Some(separator);
+        self
+    }
+
+    /// Makes `parse` return the spans of the input that each geometry and its parts were
+    /// parsed from.
+    pub fn spans(mut self) -> Self {
+        self.spans = true;
+        self
+    }
+
+    /// Instead of stopping at the first error, records it and skips to the next comma or paren
+    /// to carry on. Parsing then never fails: it gives whatever could be parsed along with all
+    /// errors, with no errors meaning that the input is valid.
+    pub fn recovering(mut self) -> Self {
+        self.recovering = true;
+        self
+    }
+
+    /// Stops after the geometry instead of requiring the input to end there, so that it can
+    /// be followed by anything. With `many`, stops before the first token that neither is a
+    /// separator nor starts a geometry. `consumed` tells where the geometries end.
+    pub fn prefix(mut self) -> Self {
+        self.prefix = true;
+        self
+    }
+
+    /// Parses `wkt_str` into geometries with coordinates stored as `T`. Empty input gives no
+    /// items.
+    pub fn parse<T: WktFloat>(&self, wkt_str: &str) -> Result<Parsed<T>, Error> {
+        if self.spans {
+            let mut builder = SpanBuilder::new();
+            let processed = self.process(wkt_str, &mut builder)?;
+            let (items, spans) = builder.finish().into_iter().unzip();
+            Ok(Parsed {
+                wkt: Wkt { items },
+                spans,
+                consumed: processed.consumed,
+                errors: processed.errors,
+            })
+        } else {
+            let mut builder = GeometryBuilder::new();
+            let processed = self.process(wkt_str, &mut builder)?;
+            Ok(Parsed {
+                wkt: Wkt {
+                    items: builder.finish(),
+                },
+                spans: Vec::new(),
+                consumed: processed.consumed,
+                errors: processed.errors,
+            })
+        }
+    }
+
+    /// Parses `wkt_str` like `parse`, but reports the geometries to `processor` instead of
+    /// building them.
+    pub fn process<T, P>(&self, wkt_str: &str, processor: &mut P) -> Result<Processed, Error>
+    where
+        T: WktFloat,
+        P: Processor<T>,
+    {
+        let mut tokens = PeekableTokens::with_options(Tokens::new(wkt_str), self.options.clone());
+        if self.recovering {
+            tokens.recover_from_errors();
+        }
+        let mut consumed = 0;
+        let result = self.process_tokens(&mut tokens, processor, &mut consumed);
+        match tokens.take_diagnostics() {
+            mut errors if self.recovering => {
+                errors.extend(result.err());
+                Ok(Processed { consumed, errors })
+            }
+            _ => result.map(|()| Processed {
+                consumed,
+                errors: Vec::new(),
+            }),
+        }
+    }
+
+    /// Returns an iterator parsing the geometries of `wkt_str` one at a time, with coordinates
+    /// stored as `T`. Spans and recovering from errors don't apply, since each geometry is
+    /// returned as soon as it has been parsed.
+    pub fn iter<'a, T: WktFloat>(&self, wkt_str: &'a str) -> Geometries<'a, T> {
+        let tokens = PeekableTokens::with_options(Tokens::new(wkt_str), self.options.clone());
+        Geometries::new(tokens, self.separator, self.prefix)
+    }
+
+    /// Parses the geometries of `tokens`, keeping `consumed` at the end of the last one.
+    fn process_tokens<T, P>(
+        &self,
+        tokens: &mut PeekableTokens,
+        processor: &mut P,
+        consumed: &mut usize,
+    ) -> Result<(), Error>
+    where
+        T: WktFloat,
+        P: Processor<T>,
+    {
+        let mut started = false;
+        loop {
+            let keyword = match next_keyword(tokens, self.separator, started) {
+                Err(_) if self.prefix && started => return Ok(()),
+                keyword => keyword?,
+            };
+            match keyword {
+                Some(word) => process_word_and_tokens(word, tokens, processor)?,
+                None => return Ok(()),
+            }
+            *consumed = tokens.last_span().end;
+            if self.prefix && self.separator.is_none() {
+                // Don't read past the geometry.
+                return Ok(());
+            }
+            started = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Parser;
+    use {Error, Geometry, Separator};
+
+    #[test]
+    fn parser_settings() {
+        let parsed = Parser::new().parse::<f64>("POINT (1 2)").ok().unwrap();
+        assert_eq!(1, parsed.wkt.items.len());
+        assert_eq!(11, parsed.consumed);
+        assert!(parsed.spans.is_empty() && parsed.errors.is_empty());
+
+        let parser = Parser::new().prefix();
+        let parsed = parser.parse::<f64>("POINT (1 2) (garbage").ok().unwrap();
+        assert_eq!(11, parsed.consumed);
+        assert_eq!(0, parser.parse::<f64>("  ").ok().unwrap().consumed);
+
+        // With several geometries, a prefix ends before the first token that can't continue
+        // the input.
+        let parser = Parser::new().many(Separator::Char(';')).prefix();
+        let parsed = parser
+            .parse::<f64>("POINT (1 2); POINT (3 4) POINT")
+            .ok()
+            .unwrap();
+        assert_eq!(2, parsed.wkt.items.len());
+        assert_eq!(24, parsed.consumed);
+        let mut geometries = parser.iter::<f64>("POINT (1 2); POINT (3 4), POINT EMPTY");
+        geometries.next().unwrap().ok().unwrap();
+        geometries.next().unwrap().ok().unwrap();
+        assert!(geometries.next().is_none());
+
+        let parser = Parser::new()
+            .many(Separator::Whitespace)
+            .recovering()
+            .spans();
+        let parsed = parser
+            .parse::<f64>("LINESTRING (1 2, 3 x, 5 6) POINT (1 2)")
+            .ok()
+            .unwrap();
+        assert_eq!(2, parsed.wkt.items.len());
+        assert_eq!(2, parsed.spans.len());
+        assert_eq!(1, parsed.errors.len());
+
+        // Without a separator, only one geometry is allowed.
+        let mut geometries = Parser::new().iter::<f64>("POINT (1 2) POINT (3 4)");
+        match geometries.next() {
+            Some(Ok(Geometry::Point(_))) => (),
+            other => panic!("expected a point, found {:?}", other),
+        }
+        match geometries.next() {
+            Some(Err(Error::UnexpectedToken { expected, .. })) => {
+                assert_eq!("end of input", expected)
+            }
+            other => panic!("expected an error, found {:?}", other),
+        }
+    }
+}
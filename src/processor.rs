@@ -0,0 +1,382 @@
+// Copyright 2015 The GeoRust Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use tokenizer::Span;
+use types::{
+    Coord, Dimension, GeometryCollection, LineString, MultiLineString, MultiPoint, MultiPolygon,
+    Point, Polygon,
+};
+use Geometry;
+use GeometryType;
+use WktFloat;
+
+/// Receives the parts of a geometry in the order the parser reads them, see
+/// `Parser::process`.
+///
+/// Every geometry is reported between a `geometry_begin` and a matching `geometry_end`, with
+/// nothing in between if it is `EMPTY`:
+///
+/// - a point reports its coordinate, if any, and a line string each of its coordinates;
+/// - a polygon reports each ring between `ring_begin` and `ring_end`;
+/// - a multi-geometry or collection reports each member as a geometry of its own, so the
+///   members of a `MULTIPOINT` are `POINT`s, and so on.
+///
+/// The calls are only balanced for input that parses. When parsing fails, it stops where the
+/// error is without ending what it began, so the last `geometry_begin` and `ring_begin` calls
+/// may have no matching end. Errors recovered from with `Parser::recovering` don't unbalance
+/// the calls, but those it cannot recover from, such as going over a limit, do.
+pub trait Processor<T: WktFloat = f64> {
+    fn geometry_begin(&mut self, kind: GeometryType, dim: Dimension);
+
+    fn coord(&mut self, x: T, y: T, z: Option<T>, m: Option<T>);
+
+    fn ring_begin(&mut self) {}
+
+    fn ring_end(&mut self) {}
+
+    fn geometry_end(&mut self);
+
+    /// Reports the byte range of the input that produced the coordinate, ring or geometry just
+    /// reported by `coord`, `ring_end` or `geometry_end`. Does nothing by default.
+    fn span(&mut self, span: Span) {
+        let _ = span;
+    }
+}
+
+/// Builds the geometries reported to it as `Geometry` values.
+pub(crate) struct GeometryBuilder<T: WktFloat> {
+    /// The geometries begun but not yet ended, innermost last.
+    stack: Vec<Geometry<T>>,
+    finished: Vec<Geometry<T>>,
+}
+
+impl<T: WktFloat> GeometryBuilder<T> {
+    pub(crate) fn new() -> Self {
+        GeometryBuilder {
+            stack: Vec::new(),
+            finished: Vec::new(),
+        }
+    }
+
+    /// Returns the outermost geometries ended since the builder was created, in order.
+    pub(crate) fn finish(self) -> Vec<Geometry<T>> {
+        self.finished
+    }
+}
+
+impl<T: WktFloat> Processor<T> for GeometryBuilder<T> {
+    fn geometry_begin(&mut self, kind: GeometryType, _dim: Dimension) {
+        let geometry = match kind {
+            GeometryType::Point => Point::default().as_item(),
+            GeometryType::LineString => LineString::default().as_item(),
+            GeometryType::Polygon => Polygon::default().as_item(),
+            GeometryType::MultiPoint => MultiPoint::default().as_item(),
+            GeometryType::MultiLineString => MultiLineString::default().as_item(),
+            GeometryType::MultiPolygon => MultiPolygon::default().as_item(),
+            GeometryType::GeometryCollection => GeometryCollection::default().as_item(),
+        };
+        self.stack.push(geometry);
+    }
+
+    fn coord(&mut self, x: T, y: T, z: Option<T>, m: Option<T>) {
+        let coord = Coord { x, y, z, m };
+        match self.stack.last_mut() {
+            Some(&mut Geometry::Point(ref mut point)) => point.0 = Some(coord),
+            Some(&mut Geometry::LineString(ref mut line)) => line.0.push(coord),
+            Some(&mut Geometry::Polygon(Polygon(ref mut rings))) => match rings.last_mut() {
+                Some(ring) => ring.0.push(coord),
+                None => unreachable!(),
+            },
+            _ => unreachable!(),
+        }
+    }
+
+    fn ring_begin(&mut self) {
+        match self.stack.last_mut() {
+            Some(&mut Geometry::Polygon(ref mut polygon)) => polygon.0.push(LineString::default()),
+            _ => unreachable!(),
+        }
+    }
+
+    fn geometry_end(&mut self) {
+        let geometry = match self.stack.pop() {
+            Some(geometry) => geometry,
+            None => unreachable!(),
+        };
+        match (self.stack.last_mut(), geometry) {
+            (None, geometry) => self.finished.push(geometry),
+            (Some(&mut Geometry::MultiPoint(ref mut parent)), Geometry::Point(point)) => {
+                parent.0.push(point)
+            }
+            (Some(&mut Geometry::MultiLineString(ref mut parent)), Geometry::LineString(line)) => {
+                parent.0.push(line)
+            }
+            (Some(&mut Geometry::MultiPolygon(ref mut parent)), Geometry::Polygon(polygon)) => {
+                parent.0.push(polygon)
+            }
+            (Some(&mut Geometry::GeometryCollection(ref mut parent)), geometry) => {
+                parent.0.push(geometry)
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// The byte ranges of the input that a geometry and each of its parts were parsed from, see
+/// `Parser::spans`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Spans {
+    /// The whole geometry, ring or coordinate, from its keyword, paren or first number to its
+    /// last paren, `EMPTY` or number.
+    pub span: Span,
+    /// The spans of the parts, in the order the geometry holds them: the coordinates of a point,
+    /// line string or ring, the rings of a polygon, or the members of a multi-geometry or
+    /// collection.
+    pub parts: Vec<Spans>,
+}
+
+/// Builds the geometries reported to it along with their `Spans`.
+pub(crate) struct SpanBuilder<T: WktFloat> {
+    geometries: GeometryBuilder<T>,
+    /// The spans of the geometries and rings begun but not yet ended, innermost last.
+    stack: Vec<Spans>,
+    finished: Vec<Spans>,
+}
+
+impl<T: WktFloat> SpanBuilder<T> {
+    pub(crate) fn new() -> Self {
+        SpanBuilder {
+            geometries: GeometryBuilder::new(),
+            stack: Vec::new(),
+            finished: Vec::new(),
+        }
+    }
+
+    /// Returns the outermost geometries ended since the builder was created, in order, each
+    /// with its spans.
+    pub(crate) fn finish(self) -> Vec<(Geometry<T>, Spans)> {
+        self.geometries
+            .finish()
+            .into_iter()
+            .zip(self.finished)
+            .collect()
+    }
+
+    fn end(&mut self) {
+        let spans = match self.stack.pop() {
+            Some(spans) => spans,
+            None => unreachable!(),
+        };
+        match self.stack.last_mut() {
+            Some(parent) => parent.parts.push(spans),
+            None => self.finished.push(spans),
+        }
+    }
+}
+
+impl<T: WktFloat> Processor<T> for SpanBuilder<T> {
+    fn geometry_begin(&mut self, kind: GeometryType, dim: Dimension) {
+        self.geometries.geometry_begin(kind, dim);
+        self.stack.push(Spans::default());
+    }
+
+    fn coord(&mut self, x: T, y: T, z: Option<T>, m: Option<T>) {
+        self.geometries.coord(x, y, z, m);
+        self.stack.push(Spans::default());
+        self.end();
+    }
+
+    fn ring_begin(&mut self) {
+        self.geometries.ring_begin();
+        self.stack.push(Spans::default());
+    }
+
+    fn ring_end(&mut self) {
+        self.geometries.ring_end();
+        self.end();
+    }
+
+    fn geometry_end(&mut self) {
+        self.geometries.geometry_end();
+        self.end();
+    }
+
+    fn span(&mut self, span: Span) {
+        let spans = match self.stack.last_mut() {
+            Some(parent) => parent.parts.last_mut(),
+            None => self.finished.last_mut(),
+        };
+        match spans {
+            Some(spans) => spans.span = span,
+            None => unreachable!(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Processor, Spans};
+    use types::Dimension;
+    use GeometryType;
+    use {Parser, Wkt};
+
+    fn parse_with_spans(wkt_str: &str) -> (Wkt, Vec<Spans>) {
+        let parsed = Parser::new().spans().parse(wkt_str).ok().unwrap();
+        (parsed.wkt, parsed.spans)
+    }
+
+    /// Records the events it receives as text.
+    struct Recorder(Vec<String>);
+
+    impl Processor<f64> for Recorder {
+        fn geometry_begin(&mut self, kind: GeometryType, dim: Dimension) {
+            self.0.push(format!("begin {:?} {:?}", kind, dim));
+        }
+
+        fn coord(&mut self, x: f64, y: f64, z: Option<f64>, m: Option<f64>) {
+            self.0.push(format!("coord {} {} {:?} {:?}", x, y, z, m));
+        }
+
+        fn ring_begin(&mut self) {
+            self.0.push("ring begin".to_string());
+        }
+
+        fn ring_end(&mut self) {
+            self.0.push("ring end".to_string());
+        }
+
+        fn geometry_end(&mut self) {
+            self.0.push("end".to_string());
+        }
+    }
+
+    #[test]
+    fn process_events() {
+        let mut recorder = Recorder(vec![]);
+        Parser::new()
+            .process(
+                "MULTIPOLYGON M (((0 0 1, 1 0 2, 0 0 3)), EMPTY)",
+                &mut recorder,
+            )
+            .ok()
+            .unwrap();
+        let expected = vec![
+            "begin MultiPolygon XYM",
+            "begin Polygon XYM",
+            "ring begin",
+            "coord 0 0 None Some(1.0)",
+            "coord 1 0 None Some(2.0)",
+            "coord 0 0 None Some(3.0)",
+            "ring end",
+            "end",
+            "begin Polygon XYM",
+            "end",
+            "end",
+        ];
+        assert_eq!(expected, recorder.0);
+
+        let mut recorder = Recorder(vec![]);
+        Parser::new()
+            .process(
+                "GEOMETRYCOLLECTION (MULTIPOINT (1 2, EMPTY), LINESTRING EMPTY)",
+                &mut recorder,
+            )
+            .ok()
+            .unwrap();
+        let expected = vec![
+            "begin GeometryCollection XY",
+            "begin MultiPoint XY",
+            "begin Point XY",
+            "coord 1 2 None None",
+            "end",
+            "begin Point XY",
+            "end",
+            "end",
+            "begin LineString XY",
+            "end",
+            "end",
+        ];
+        assert_eq!(expected, recorder.0);
+
+        // The declared dimension is reported even without coordinates to carry it.
+        let mut recorder = Recorder(vec![]);
+        Parser::new()
+            .process("GEOMETRYCOLLECTION Z (POINTZ EMPTY)", &mut recorder)
+            .ok()
+            .unwrap();
+        let expected = vec![
+            "begin GeometryCollection XYZ",
+            "begin Point XYZ",
+            "end",
+            "end",
+        ];
+        assert_eq!(expected, recorder.0);
+
+        let mut recorder = Recorder(vec![]);
+        Parser::new()
+            .process("POINT (1 2) POINT", &mut recorder)
+            .err()
+            .unwrap();
+
+        // A failed parse leaves what it began unended.
+        let mut recorder = Recorder(vec![]);
+        Parser::new()
+            .process("POLYGON ((0 0, 1 x", &mut recorder)
+            .err()
+            .unwrap();
+        let expected = vec!["begin Polygon XY", "ring begin", "coord 0 0 None None"];
+        assert_eq!(expected, recorder.0);
+
+        // Recovering from the error ends everything.
+        let mut recorder = Recorder(vec![]);
+        Parser::new()
+            .recovering()
+            .process("GEOMETRYCOLLECTION (POLYGON ((0 0, 1 x", &mut recorder)
+            .ok()
+            .unwrap();
+        let count = |prefix| recorder.0.iter().filter(|e| e.starts_with(prefix)).count();
+        assert_eq!((2, 1), (count("begin"), count("ring begin")));
+        assert_eq!((2, 1), (count("end"), count("ring end")));
+    }
+
+    #[test]
+    fn spans() {
+        let input = "POLYGON ((0 0, 10 0, 0 10, 0 0), EMPTY)";
+        let (wkt, spans) = parse_with_spans(input);
+        assert_eq!(1, wkt.items.len());
+        assert_eq!(1, spans.len());
+        let slice = |spans: &Spans| &input[spans.span.start..spans.span.end];
+        assert_eq!(input, slice(&spans[0]));
+        let rings = &spans[0].parts;
+        assert_eq!(2, rings.len());
+        assert_eq!("(0 0, 10 0, 0 10, 0 0)", slice(&rings[0]));
+        assert_eq!("EMPTY", slice(&rings[1]));
+        assert!(rings[1].parts.is_empty());
+        let vertices: Vec<&str> = rings[0].parts.iter().map(slice).collect();
+        assert_eq!(vec!["0 0", "10 0", "0 10", "0 0"], vertices);
+
+        let input = "GEOMETRYCOLLECTION (MULTIPOINT ((1 2), 3 4), POINT EMPTY)";
+        let (_, spans) = parse_with_spans(input);
+        let slice = |spans: &Spans| &input[spans.span.start..spans.span.end];
+        let members = &spans[0].parts;
+        assert_eq!("MULTIPOINT ((1 2), 3 4)", slice(&members[0]));
+        assert_eq!("POINT EMPTY", slice(&members[1]));
+        let points: Vec<&str> = members[0].parts.iter().map(slice).collect();
+        assert_eq!(vec!["(1 2)", "3 4"], points);
+        assert_eq!("1 2", slice(&members[0].parts[0].parts[0]));
+
+        let (wkt, spans) = parse_with_spans("");
+        assert!(wkt.items.is_empty() && spans.is_empty());
+    }
+}
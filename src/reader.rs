@@ -0,0 +1,482 @@
+// Copyright 2015 The GeoRust Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::{self, BufRead};
+use std::marker::PhantomData;
+use std::str;
+use tokenizer::{PeekableTokens, Span, Token, Tokens};
+use Error;
+use Geometry;
+use Limit;
+use ParseOptions;
+use Position;
+use WktFloat;
+
+/// What separates consecutive geometries in an input holding several of them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Separator {
+    /// Geometries follow each other with optional whitespace in between.
+    Whitespace,
+    /// Each geometry starts on a new line.
+    Newline,
+    /// Geometries are separated by a non-whitespace character such as `;`. A trailing
+    /// separator after the last geometry is allowed.
+    Char(char),
+}
+
+/// Reads the keyword starting the next geometry of `tokens`, after the separator if an
+/// earlier geometry was read. Returns `None` at the end of the input. Without a separator,
+/// there can be no geometry after the first one.
+pub(crate) fn next_keyword<'a>(
+    tokens: &mut PeekableTokens<'a>,
+    separator: Option<Separator>,
+    started: bool,
+) -> Result<Option<&'a str>, Error> {
+    let previous_end = tokens.last_span().end;
+    let mut token = tokens.next()?;
+    if started && token.is_some() {
+        let span = tokens.last_span();
+        match separator {
+            None => {
+                return Err(Error::unexpected(
+                    "end of input",
+                    token,
+                    tokens.last_position(),
+                ))
+            }
+            Some(Separator::Whitespace) => (),
+            Some(Separator::Newline) => {
+                let gap = tokens.slice(Span {
+                    start: previous_end,
+                    end: span.start,
+                });
+                if !gap.contains('\n') {
+                    return Err(Error::unexpected(
+                        "a line break",
+                        token,
+                        tokens.last_position(),
+                    ));
+                }
+            }
+            Some(Separator::Char(c)) => {
+                let mut buf = [0; 4];
+                if tokens.slice(span) != c.encode_utf8(&mut buf) {
+                    return Err(Error::unexpected(
+                        "a separator",
+                        token,
+                        tokens.last_position(),
+                    ));
+                }
+                token = tokens.next()?;
+            }
+        }
+    }
+    match token {
+        Some(Token::Word(word)) => Ok(Some(word)),
+        None => Ok(None),
+        other => Err(Error::unexpected(
+            "a geometry type",
+            other,
+            tokens.last_position(),
+        )),
+    }
+}
+
+/// An iterator over the geometries of an input, created with `Parser::iter`.
+///
+/// Iteration stops after the first error.
+pub struct Geometries<'a, T: WktFloat = f64> {
+    tokens: PeekableTokens<'a>,
+    separator: Option<Separator>,
+    prefix: bool,
+    started: bool,
+    done: bool,
+    scalar: PhantomData<T>,
+}
+
+impl<'a, T: WktFloat> Geometries<'a, T> {
+    pub(crate) fn new(
+        tokens: PeekableTokens<'a>,
+        separator: Option<Separator>,
+        prefix: bool,
+    ) -> Self {
+        Geometries {
+            tokens,
+            separator,
+            prefix,
+            started: false,
+            done: false,
+            scalar: PhantomData,
+        }
+    }
+
+    /// Treats the input as the continuation of an input after an earlier geometry, so that it
+    /// has to start with a separator.
+    fn continued(mut self) -> Self {
+        self.started = true;
+        self
+    }
+
+    fn next_geometry(&mut self) -> Result<Option<Geometry<T>>, Error> {
+        let keyword = match next_keyword(&mut self.tokens, self.separator, self.started) {
+            Err(_) if self.prefix && self.started => return Ok(None),
+            keyword => keyword?,
+        };
+        self.started = true;
+        match keyword {
+            Some(word) => Geometry::from_word_and_tokens(word, &mut self.tokens).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+impl<'a, T: WktFloat> Iterator for Geometries<'a, T> {
+    type Item = Result<Geometry<T>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let result = self.next_geometry();
+        match result {
+            Ok(Some(geometry)) => Some(Ok(geometry)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(err) => {
+                self.done = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+/// Reads the geometries of an input holding several of them from a `BufRead`, parsing each
+/// one as soon as it has been read in full.
+///
+/// Only the text of the geometry being read is buffered, so memory use is bounded by the
+/// largest geometry rather than by the size of the input. Error positions are relative to the
+/// start of the input. Iteration stops after the first error.
+pub struct WktReader<R, T: WktFloat = f64> {
+    reader: R,
+    separator: Separator,
+    options: ParseOptions,
+    /// Input read but not yet parsed.
+    buf: Vec<u8>,
+    /// Where `buf` starts in the input.
+    base: Position,
+    scan: Scan,
+    started: bool,
+    done: bool,
+    scalar: PhantomData<T>,
+}
+
+/// Progress of the search for the end of the next geometry in the buffer.
+#[derive(Default)]
+struct Scan {
+    /// How far the buffer has been searched.
+    offset: usize,
+    /// Paren nesting depth at `offset`.
+    depth: usize,
+    /// Start of the word being read at `offset`, if any.
+    word_start: Option<usize>,
+}
+
+impl<R: BufRead, T: WktFloat> WktReader<R, T> {
+    pub fn new(reader: R, separator: Separator) -> Self {
+        WktReader::with_options(reader, separator, ParseOptions::default())
+    }
+
+    /// Like `new`, but with explicit parser settings.
+    pub fn with_options(reader: R, separator: Separator, options: ParseOptions) -> Self {
+        WktReader {
+            reader,
+            separator,
+            options,
+            buf: Vec::new(),
+            base: Position {
+                offset: 0,
+                line: 1,
+                column: 1,
+            },
+            scan: Scan::default(),
+            started: false,
+            done: false,
+            scalar: PhantomData,
+        }
+    }
+
+    /// Searches the unsearched part of the buffer for the end of the next geometry: the paren
+    /// closing its outermost one, or the end of an `EMPTY` outside of any.
+    fn find_end(&mut self) -> Option<usize> {
+        let scan = &mut self.scan;
+        while scan.offset < self.buf.len() {
+            let byte = self.buf[scan.offset];
+            scan.offset += 1;
+            if byte.is_ascii_alphanumeric() || byte == b'_' {
+                if scan.word_start.is_none() {
+                    scan.word_start = Some(scan.offset - 1);
+                }
+                continue;
+            }
+            if let Some(start) = scan.word_start.take() {
+                let word = &self.buf[start..scan.offset - 1];
+                if scan.depth == 0 && word.eq_ignore_ascii_case(b"EMPTY") {
+                    return Some(scan.offset - 1);
+                }
+            }
+            match byte {
+                b'(' => scan.depth += 1,
+                b')' if scan.depth <= 1 => return Some(scan.offset),
+                b')' => scan.depth -= 1,
+                _ => (),
+            }
+        }
+        None
+    }
+
+    /// Reads more input into the buffer, returning `false` at the end of the input.
+    fn fill(&mut self) -> Result<bool, Error> {
+        let read = loop {
+            match self.reader.fill_buf() {
+                Ok(chunk) => {
+                    self.buf.extend_from_slice(chunk);
+                    break chunk.len();
+                }
+                Err(ref err) if err.kind() == io::ErrorKind::Interrupted => (),
+                Err(ref err) => {
+                    let position = self.base.after(&String::from_utf8_lossy(&self.buf));
+                    return Err(Error::io(err, position));
+                }
+            }
+        };
+        self.reader.consume(read);
+        Ok(read > 0)
+    }
+
+    /// Parses the first `len` bytes of the buffer and removes them from it.
+    fn parse(&mut self, len: usize) -> Result<Option<Geometry<T>>, Error> {
+        let base = self.base;
+        let result = {
+            let text = str::from_utf8(&self.buf[..len]).map_err(|err| {
+                let valid = str::from_utf8(&self.buf[..err.valid_up_to()]).unwrap_or_default();
+                let message = "stream did not contain valid UTF-8";
+                let err = io::Error::new(io::ErrorKind::InvalidData, message);
+                Error::io(&err, base.after(valid))
+            })?;
+            self.base = base.after(text);
+            let tokens = PeekableTokens::with_options(Tokens::new(text), self.options.clone());
+            let mut geometries = Geometries::new(tokens, Some(self.separator), false);
+            if self.started {
+                geometries = geometries.continued();
+            }
+            geometries.next().transpose()
+        };
+        self.started = true;
+        self.buf.drain(..len);
+        self.scan = Scan::default();
+        result.map_err(|mut err| {
+            let position = err.position().within(base);
+            *err.position_mut() = position;
+            err
+        })
+    }
+
+    fn next_geometry(&mut self) -> Result<Option<Geometry<T>>, Error> {
+        loop {
+            if let Some(end) = self.find_end() {
+                return self.parse(end);
+            }
+            if let Some(max) = self.options.max_input_len {
+                if self.buf.len() > max {
+                    return Err(Error::LimitExceeded {
+                        limit: Limit::InputLen,
+                        max,
+                        position: self.base,
+                    });
+                }
+            }
+            if !self.fill()? {
+                // Whatever is left either is a final geometry ending in `EMPTY`, or has to be
+                // only whitespace and separators.
+                self.done = true;
+                let len = self.buf.len();
+                return self.parse(len);
+            }
+        }
+    }
+}
+
+impl<R: BufRead, T: WktFloat> Iterator for WktReader<R, T> {
+    type Item = Result<Geometry<T>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let result = self.next_geometry();
+        if result.is_err() {
+            self.done = true;
+        }
+        result.transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Separator, WktReader};
+    use std::io::{self, BufReader};
+    use types::Point;
+    use {Error, Geometry, ParseOptions, Parser, Position, Wkt};
+
+    fn parse_many(input: &str, separator: Separator) -> Result<Wkt, Error> {
+        Parser::new()
+            .many(separator)
+            .parse(input)
+            .map(|parsed| parsed.wkt)
+    }
+
+    #[test]
+    fn geometries_per_line() {
+        let input = "POINT (1 2)\nLINESTRING (1 2, 3 4)\r\n\n  POINT EMPTY\n";
+        let wkt = parse_many(input, Separator::Newline).ok().unwrap();
+        assert_eq!(3, wkt.items.len());
+        match wkt.items[2] {
+            Geometry::Point(Point(None)) => (),
+            _ => unreachable!(),
+        };
+
+        parse_many("POINT (1 2) POINT (3 4)", Separator::Newline)
+            .err()
+            .unwrap();
+    }
+
+    #[test]
+    fn geometries_with_separator() {
+        let input = "POINT (1 2); POINT EMPTY;POLYGON ((0 0, 1 0, 1 1, 0 0));";
+        let wkt = parse_many(input, Separator::Char(';')).ok().unwrap();
+        assert_eq!(3, wkt.items.len());
+
+        parse_many("POINT (1 2) POINT (3 4)", Separator::Char(';'))
+            .err()
+            .unwrap();
+        parse_many("POINT (1 2);; POINT (3 4)", Separator::Char(';'))
+            .err()
+            .unwrap();
+    }
+
+    #[test]
+    fn reader_in_small_chunks() {
+        let input = "POINT (1 2)\nLINESTRING (1 2, 3 4)\r\n\n  POINT EMPTY\nMULTIPOINT EMPTY";
+        let reader = BufReader::with_capacity(3, input.as_bytes());
+        let items = WktReader::new(reader, Separator::Newline)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let wkt = parse_many(input, Separator::Newline).ok().unwrap();
+        assert_eq!(wkt.items, items);
+
+        let input = "POINT (1 2); GEOMETRYCOLLECTION (POINT EMPTY, POINT (3 4)) ;";
+        let reader = BufReader::with_capacity(1, input.as_bytes());
+        let items = WktReader::<_, f64>::new(reader, Separator::Char(';'))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(2, items.len());
+    }
+
+    #[test]
+    fn reader_errors() {
+        let input = "POINT (1 2)\nPOINT (1 x)\nPOINT (3 4)\n";
+        let mut reader = WktReader::<_, f64>::new(input.as_bytes(), Separator::Newline);
+        reader.next().unwrap().ok().unwrap();
+        let err = reader.next().unwrap().err().unwrap();
+        assert_eq!(
+            Position {
+                offset: 19,
+                line: 2,
+                column: 8,
+            },
+            err.position()
+        );
+        let expected = parse_many(input, Separator::Newline).err().unwrap();
+        assert_eq!(expected, err);
+        assert!(reader.next().is_none());
+
+        let mut reader =
+            WktReader::<_, f64>::new(&b"POINT (1 2)\nPOINT (\xff"[..], Separator::Newline);
+        reader.next().unwrap().ok().unwrap();
+        match reader.next().unwrap() {
+            Err(Error::Io { kind, position, .. }) => {
+                assert_eq!(io::ErrorKind::InvalidData, kind);
+                assert_eq!(19, position.offset);
+            }
+            _ => unreachable!(),
+        }
+
+        let mut reader = WktReader::<_, f64>::new("POINT (1 2".as_bytes(), Separator::Newline);
+        reader.next().unwrap().err().unwrap();
+
+        // The length limit bounds the text buffered for each geometry.
+        let options = ParseOptions {
+            max_input_len: Some(16),
+            ..Default::default()
+        };
+        let input = "POINT (1 2)\nPOINT (1 2)\nLINESTRING (1 2, 3 4)\n";
+        let reader = BufReader::with_capacity(4, input.as_bytes());
+        let mut reader = WktReader::<_, f64>::with_options(reader, Separator::Newline, options);
+        reader.next().unwrap().ok().unwrap();
+        reader.next().unwrap().ok().unwrap();
+        match reader.next().unwrap() {
+            Err(Error::LimitExceeded { position, .. }) => assert_eq!(23, position.offset),
+            _ => unreachable!(),
+        }
+
+        // So does the coordinate limit.
+        let options = ParseOptions {
+            max_coords: Some(2),
+            ..Default::default()
+        };
+        let input = "MULTIPOINT (1 2, 3 4)\nMULTIPOINT (1 2, 3 4)\nMULTIPOINT (1 2, 3 4, 5 6)";
+        let reader =
+            WktReader::<_, f64>::with_options(input.as_bytes(), Separator::Newline, options);
+        let geometries: Vec<_> = reader.collect();
+        assert_eq!(3, geometries.len());
+        assert!(geometries[1].is_ok());
+        match geometries[2] {
+            Err(Error::LimitExceeded { position, .. }) => assert_eq!(66, position.offset),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn geometries_iterator() {
+        let input = "POINT (1 2) POINT (3 4)POINT 5";
+        let mut geometries = Parser::new().many(Separator::Whitespace).iter::<f64>(input);
+        geometries.next().unwrap().ok().unwrap();
+        geometries.next().unwrap().ok().unwrap();
+        geometries.next().unwrap().err().unwrap();
+        assert!(geometries.next().is_none());
+
+        let mut geometries = Parser::new().many(Separator::Whitespace).iter::<f64>("  ");
+        assert!(geometries.next().is_none());
+
+        let mut geometries = Parser::new()
+            .many(Separator::Newline)
+            .iter::<f32>("POINT (0.1 2)");
+        match geometries.next() {
+            Some(Ok(Geometry::Point(Point(Some(coord))))) => assert_eq!(0.1f32, coord.x),
+            other => panic!("expected a point, found {:?}", other),
+        }
+    }
+}
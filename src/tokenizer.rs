@@ -12,159 +12,583 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::iter::Peekable;
+//! The tokenizer the WKT parser is built on, for crates reading WKT-like grammars of their own,
+//! such as custom keywords or CRS WKT.
+//!
+//! `Tokens` splits text into `Token`s, skipping whitespace and recording the `Span` of each
+//! token. `PeekableTokens` adds a token of lookahead and tracks paren nesting.
+//!
+//! Whitespace is spaces, tabs, `\r` and `\n`; any other character, Unicode whitespace and NUL
+//! included, is part of a token. Numbers follow the grammar described at `Tokens::next`.
+//!
+//! ```
+//! use wkt::tokenizer::{Token, TokenKind, Tokens};
+//!
+//! let mut tokens = Tokens::new("POINT (1 -2.5e1)");
+//! assert_eq!(Some(TokenKind::Word), tokens.peek().map(|token| token.unwrap().kind()));
+//! assert_eq!(Some(Ok(Token::Word("POINT"))), tokens.next());
+//! assert_eq!((0, 5), (tokens.last_span().start, tokens.last_span().end));
+//! let rest: Vec<Token> = tokens.collect::<Result<_, _>>().unwrap();
+//! assert_eq!(
+//!     vec![Token::ParenOpen, Token::Number(1.0), Token::Number(-25.0), Token::ParenClose],
+//!     rest
+//! );
+//! ```
 
-#[derive(PartialEq, Debug)]
-pub enum Token {
+use std::fmt;
+
+use error::{Error, Position};
+use options::{Limit, ParseOptions};
+
+/// A token of WKT text.
+///
+/// More kinds of tokens may be added, such as for the brackets and quoted strings of CRS WKT,
+/// which are read as single character words for now.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[non_exhaustive]
+pub enum Token<'a> {
     Comma,
+    /// A number, including a signed or unsigned `NaN`, `Inf` or `Infinity`.
     Number(f64),
     ParenClose,
     ParenOpen,
-    Word(String),
+    /// A run of ASCII letters, digits and underscores not starting like a number, or any
+    /// single character that is not otherwise part of the grammar, such as `;`.
+    Word(&'a str),
+}
+
+/// The kind of a `Token`, without its value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum TokenKind {
+    Comma,
+    Number,
+    ParenClose,
+    ParenOpen,
+    Word,
 }
 
-fn is_whitespace(c: char) -> bool {
-    match c {
-        '\n' | '\r' | '\t' | ' ' => true,
-        _ => false,
+impl<'a> Token<'a> {
+    pub fn kind(&self) -> TokenKind {
+        match *self {
+            Token::Comma => TokenKind::Comma,
+            Token::Number(_) => TokenKind::Number,
+            Token::ParenClose => TokenKind::ParenClose,
+            Token::ParenOpen => TokenKind::ParenOpen,
+            Token::Word(_) => TokenKind::Word,
+        }
     }
 }
 
-fn is_numberlike(c: char) -> bool {
-    match c {
-        c if c.is_numeric() => true,
-        '.' | '-' | '+' => true,
-        _ => false,
+impl<'a> fmt::Display for Token<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Token::Comma => f.write_str(","),
+            Token::Number(n) => write!(f, "{}", n),
+            Token::ParenClose => f.write_str(")"),
+            Token::ParenOpen => f.write_str("("),
+            Token::Word(w) => f.write_str(w),
+        }
     }
 }
 
-pub type PeekableTokens = Peekable<Tokens>;
+fn is_whitespace(b: u8) -> bool {
+    matches!(b, b'\n' | b'\r' | b'\t' | b' ')
+}
+
+fn is_numberlike(b: u8) -> bool {
+    matches!(b, b'0'..=b'9' | b'.' | b'-' | b'+')
+}
+
+fn is_word_char(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_'
+}
+
+fn is_non_finite(word: &str) -> bool {
+    ["NaN", "Inf", "Infinity"]
+        .iter()
+        .any(|keyword| keyword.eq_ignore_ascii_case(word))
+}
 
-pub struct Tokens {
-    text: String,
+/// Returns the end of the run of ASCII digits starting at `i`.
+fn skip_digits(bytes: &[u8], mut i: usize) -> usize {
+    while i < bytes.len() && bytes[i].is_ascii_digit() {
+        i += 1;
+    }
+    i
 }
 
-impl Tokens {
-    pub fn from_str(input: &str) -> Self {
+fn is_delimiter(b: u8) -> bool {
+    matches!(b, b'(' | b')' | b',') || is_whitespace(b)
+}
+
+/// A byte range of the input.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Splits WKT text into tokens, borrowing words from the input.
+///
+/// Cloning a `Tokens` is cheap, and gives a stream that continues from the same place
+/// independently, for looking ahead any number of tokens.
+#[derive(Clone, Debug)]
+pub struct Tokens<'a> {
+    text: &'a str,
+    offset: usize,
+    span: Span,
+}
+
+impl<'a> Tokens<'a> {
+    /// Tokenizes `input`.
+    pub fn new(input: &'a str) -> Self {
         Tokens {
-            text: input.to_string(),
+            text: input,
+            offset: 0,
+            span: Span::default(),
         }
     }
-}
 
-impl Iterator for Tokens {
-    type Item = Token;
+    /// The span of the token most recently returned by `next`, or an empty span at the end of
+    /// the input once it has been exhausted.
+    pub fn last_span(&self) -> Span {
+        self.span
+    }
+
+    /// Returns the next token without consuming it.
+    pub fn peek(&self) -> Option<Result<Token<'a>, Error>> {
+        self.clone().next()
+    }
 
-    fn next(&mut self) -> Option<Token> {
-        // TODO: should this return Result?
-        let next_char = match self.pop_front() {
-            Some(c) => c,
-            None => return None,
-        };
+    /// The line and column of a byte offset into the input.
+    pub fn position_at(&self, offset: usize) -> Position {
+        Position::from_offset(self.text, offset)
+    }
 
-        match next_char {
-            '\0' => None,
-            '(' => Some(Token::ParenOpen),
-            ')' => Some(Token::ParenClose),
-            ',' => Some(Token::Comma),
-            c if is_whitespace(c) => self.next(),
-            c if is_numberlike(c) => {
-                let mut number = c.to_string() + &self.read_until_whitespace();
-                number = number.trim_left_matches('+').to_string();
-                match number.parse::<f64>() {
-                    Ok(parsed_num) => Some(Token::Number(parsed_num)),
-                    Err(e) => panic!("Could not parse number: {}", e),
+    /// The length of the whole input in bytes.
+    pub fn input_len(&self) -> usize {
+        self.text.len()
+    }
+
+    /// The input text covered by `span`.
+    pub fn slice(&self, span: Span) -> &'a str {
+        &self.text[span.start..span.end]
+    }
+
+    fn read_word(&mut self) -> &'a str {
+        let bytes = self.text.as_bytes();
+        let start = self.offset;
+        while self.offset < bytes.len() && is_word_char(bytes[self.offset]) {
+            self.offset += 1;
+        }
+        &self.text[start..self.offset]
+    }
+
+    /// Reads a number following the grammar of ISO 13249-3 and OGC 06-103r4:
+    ///
+    /// ```text
+    /// [sign] (digits [. [digits]] | . digits) [(E | e) [sign] digits]
+    /// ```
+    ///
+    /// A signed or unsigned `NaN`, `Inf` or `Infinity` is also read as a number. Whether such
+    /// values are allowed is up to the parser, see `ParseOptions::non_finite`.
+    fn read_number(&mut self) -> Result<f64, Error> {
+        let bytes = self.text.as_bytes();
+        let start = self.offset;
+        let mut end = start;
+        if end < bytes.len() && (bytes[end] == b'+' || bytes[end] == b'-') {
+            end += 1;
+        }
+        let integer_end = skip_digits(bytes, end);
+        let mut has_digits = integer_end > end;
+        end = integer_end;
+        if end < bytes.len() && bytes[end] == b'.' {
+            let fraction_end = skip_digits(bytes, end + 1);
+            has_digits |= fraction_end > end + 1;
+            end = fraction_end;
+        }
+        if has_digits {
+            if end < bytes.len() && (bytes[end] == b'e' || bytes[end] == b'E') {
+                let mut exponent = end + 1;
+                if exponent < bytes.len() && (bytes[exponent] == b'+' || bytes[exponent] == b'-') {
+                    exponent += 1;
+                }
+                let exponent_end = skip_digits(bytes, exponent);
+                if exponent_end > exponent {
+                    end = exponent_end;
                 }
             }
-            c => {
-                let word = c.to_string() + &self.read_until_whitespace();
-                Some(Token::Word(word))
+        } else if end == start + 1 {
+            // A lone sign, possibly followed by a non-finite keyword.
+            let mut word_end = end;
+            while word_end < bytes.len() && is_word_char(bytes[word_end]) {
+                word_end += 1;
             }
+            if is_non_finite(&self.text[end..word_end]) {
+                end = word_end;
+                has_digits = true;
+            }
+        }
+
+        let run_on = end < bytes.len()
+            && (is_word_char(bytes[end]) || matches!(bytes[end], b'.' | b'+' | b'-'));
+        if has_digits && !run_on {
+            if let Ok(number) = self.text[start..end].parse::<f64>() {
+                self.offset = end;
+                return Ok(number);
+            }
+        }
+        Err(Error::InvalidNumber {
+            found: self.read_until_delimiter().to_string(),
+            position: self.position_at(start),
+        })
+    }
+
+    fn read_until_delimiter(&mut self) -> &'a str {
+        let bytes = self.text.as_bytes();
+        let start = self.offset;
+        while self.offset < bytes.len() && !is_delimiter(bytes[self.offset]) {
+            self.offset += 1;
         }
+        &self.text[start..self.offset]
     }
 }
 
-impl Tokens {
-    fn pop_front(&mut self) -> Option<char> {
-        match self.text.is_empty() {
-            true => None,
-            false => Some(self.text.remove(0)),
+impl<'a> Iterator for Tokens<'a> {
+    type Item = Result<Token<'a>, Error>;
+
+    /// Skips whitespace and reads the next token, or returns `None` at the end of the input.
+    ///
+    /// A number is read following the grammar of ISO 13249-3 and OGC 06-103r4, `[sign]
+    /// (digits [. [digits]] | . digits) [(E | e) [sign] digits]`, or is a signed or unsigned
+    /// `NaN`, `Inf` or `Infinity` in any case. A number running into other characters, as in
+    /// `1-2` or `1abc`, is an `Error::InvalidNumber` covering the text up to the next
+    /// delimiter, after which reading continues.
+    fn next(&mut self) -> Option<Self::Item> {
+        let bytes = self.text.as_bytes();
+        while self.offset < bytes.len() && is_whitespace(bytes[self.offset]) {
+            self.offset += 1;
         }
+        self.span = Span {
+            start: self.offset,
+            end: self.offset,
+        };
+
+        let token = match *bytes.get(self.offset)? {
+            b'(' => Token::ParenOpen,
+            b')' => Token::ParenClose,
+            b',' => Token::Comma,
+            b if is_numberlike(b) => {
+                let number = self.read_number();
+                self.span.end = self.offset;
+                return Some(number.map(Token::Number));
+            }
+            b if is_word_char(b) => {
+                let word = self.read_word();
+                self.span.end = self.offset;
+                if is_non_finite(word) {
+                    return Some(word.parse::<f64>().map(Token::Number).map_err(|_| {
+                        Error::InvalidNumber {
+                            found: word.to_string(),
+                            position: self.position_at(self.span.start),
+                        }
+                    }));
+                }
+                return Some(Ok(Token::Word(word)));
+            }
+            _ => {
+                // Any other character stands on its own, so that e.g. a `;` following a
+                // geometry is not glued onto its last word.
+                let len = self.text[self.offset..].chars().next()?.len_utf8();
+                self.offset += len;
+                self.span.end = self.offset;
+                return Some(Ok(Token::Word(&self.text[self.span.start..self.offset])));
+            }
+        };
+        self.offset += 1;
+        self.span.end = self.offset;
+        Some(Ok(token))
+    }
+}
+
+/// A `Tokens` stream with one token of lookahead, along with the options of the parser
+/// consuming it. It keeps count of the parens opened and not yet closed by the tokens it
+/// returns, and enforces `ParseOptions::max_input_len`.
+pub struct PeekableTokens<'a> {
+    tokens: Tokens<'a>,
+    peeked: Option<(Option<Result<Token<'a>, Error>>, Span)>,
+    span: Span,
+    options: ParseOptions,
+    /// How many geometries are being parsed, each nested inside the previous one.
+    depth: usize,
+    /// How many coordinates have been parsed so far.
+    coords: usize,
+    /// How many parens returned by `next` are still open.
+    parens: usize,
+    /// The errors recovered from so far, if the parser is recovering from errors.
+    diagnostics: Option<Vec<Error>>,
+}
+
+impl<'a> PeekableTokens<'a> {
+    /// Wraps `tokens`, with the default `ParseOptions`.
+    pub fn new(tokens: Tokens<'a>) -> Self {
+        PeekableTokens::with_options(tokens, ParseOptions::default())
     }
 
-    fn read_until_whitespace(&mut self) -> String {
-        let next_char = match self.pop_front() {
-            Some(c) => c,
-            None => return "".to_string(),
+    /// Like `new`, but with explicit parser settings. Fails on the first token if the input is
+    /// over the length limit.
+    pub fn with_options(tokens: Tokens<'a>, options: ParseOptions) -> Self {
+        let span = tokens.last_span();
+        let peeked = match options.max_input_len {
+            Some(max) if tokens.input_len() > max => {
+                let err = Error::LimitExceeded {
+                    limit: Limit::InputLen,
+                    max,
+                    position: tokens.position_at(0),
+                };
+                Some((Some(Err(err)), span))
+            }
+            _ => None,
         };
+        PeekableTokens {
+            tokens,
+            peeked,
+            span,
+            options,
+            depth: 0,
+            coords: 0,
+            parens: 0,
+            diagnostics: None,
+        }
+    }
+
+    /// Makes the parser record errors with `recover` and carry on instead of failing.
+    pub(crate) fn recover_from_errors(&mut self) {
+        self.diagnostics = Some(Vec::new());
+    }
+
+    /// Records `err` and returns `Ok` if the parser is recovering from errors, or returns
+    /// `err` otherwise. The caller is then responsible for skipping past the problem.
+    pub(crate) fn recover(&mut self, err: Error) -> Result<(), Error> {
+        match self.diagnostics {
+            Some(ref mut diagnostics) => {
+                diagnostics.push(err);
+                Ok(())
+            }
+            None => Err(err),
+        }
+    }
+
+    /// The errors recorded by `recover`, in the order they occurred.
+    pub(crate) fn take_diagnostics(&mut self) -> Vec<Error> {
+        self.diagnostics.take().unwrap_or_default()
+    }
+
+    /// How many parens are open after the token most recently returned by `next`.
+    pub fn paren_depth(&self) -> usize {
+        self.parens
+    }
+
+    /// Skips tokens, and the errors in place of any, up to the next comma inside exactly
+    /// `depth` parens, or up to and including the paren closing the innermost of those.
+    pub(crate) fn skip_to(&mut self, depth: usize) {
+        while self.parens >= depth {
+            let at_depth = self.parens == depth;
+            match self.peek() {
+                Ok(Some(&Token::Comma)) if at_depth => return,
+                Ok(None) => return,
+                _ => {
+                    let _ = self.next();
+                }
+            }
+        }
+    }
 
-        match next_char {
-            '\0' | '(' | ')' | ',' => {
-                self.text.insert(0, next_char);
-                "".to_string()
+    /// Like `skip_to`, but skips commas as well.
+    pub(crate) fn skip_past(&mut self, depth: usize) {
+        loop {
+            self.skip_to(depth);
+            if self.parens < depth {
+                return;
             }
-            c if is_whitespace(c) => "".to_string(),
-            _ => next_char.to_string() + &self.read_until_whitespace(),
+            match self.next() {
+                Ok(Some(Token::Comma)) => (),
+                _ => return,
+            }
+        }
+    }
+
+    /// The options of the parser consuming the tokens.
+    pub fn options(&self) -> &ParseOptions {
+        &self.options
+    }
+
+    /// Starts parsing a geometry nested in the ones being parsed, if any.
+    pub(crate) fn enter_geometry(&mut self) -> Result<(), Error> {
+        match self.options.max_depth {
+            Some(max) if self.depth >= max => {
+                return Err(Error::LimitExceeded {
+                    limit: Limit::Depth,
+                    max,
+                    position: self.last_position(),
+                })
+            }
+            _ => (),
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    pub(crate) fn exit_geometry(&mut self) {
+        self.depth -= 1;
+    }
+
+    /// Counts a coordinate starting at `offset` towards the coordinate limit.
+    pub(crate) fn add_coord(&mut self, offset: usize) -> Result<(), Error> {
+        self.coords += 1;
+        match self.options.max_coords {
+            Some(max) if self.coords > max => Err(Error::LimitExceeded {
+                limit: Limit::Coords,
+                max,
+                position: self.position_at(offset),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the next token without consuming it. A tokenizer error is returned here as well
+    /// as from the following call to `next`.
+    pub fn peek(&mut self) -> Result<Option<&Token<'a>>, Error> {
+        if self.peeked.is_none() {
+            let token = self.tokens.next();
+            self.peeked = Some((token, self.tokens.last_span()));
+        }
+        match self.peeked {
+            Some((Some(Ok(ref token)), _)) => Ok(Some(token)),
+            Some((Some(Err(ref err)), _)) => Err(err.clone()),
+            Some((None, _)) => Ok(None),
+            None => unreachable!(),
         }
     }
+
+    /// Consumes the next token, returning `Ok(None)` at the end of the input.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Result<Option<Token<'a>>, Error> {
+        let (token, span) = match self.peeked.take() {
+            Some(peeked) => peeked,
+            None => {
+                let token = self.tokens.next();
+                (token, self.tokens.last_span())
+            }
+        };
+        self.span = span;
+        match token {
+            Some(Ok(Token::ParenOpen)) => self.parens += 1,
+            Some(Ok(Token::ParenClose)) => self.parens = self.parens.saturating_sub(1),
+            _ => (),
+        }
+        token.transpose()
+    }
+
+    /// Returns the tokens from the next one on, without consuming any of them.
+    pub fn lookahead(&mut self) -> impl Iterator<Item = Result<Token<'a>, Error>> + 'a {
+        let _ = self.peek();
+        let next = match self.peeked {
+            Some((ref token, _)) => token.clone(),
+            None => unreachable!(),
+        };
+        next.into_iter().chain(self.tokens.clone())
+    }
+
+    /// Where the next token starts, or the end of the input if there are none.
+    pub fn next_offset(&mut self) -> usize {
+        let _ = self.peek();
+        match self.peeked {
+            Some((_, span)) => span.start,
+            None => unreachable!(),
+        }
+    }
+
+    /// The span from `start` to the end of the token most recently returned by `next`.
+    pub(crate) fn span_from(&self, start: usize) -> Span {
+        Span {
+            start,
+            end: self.span.end,
+        }
+    }
+
+    /// The span of the token most recently returned by `next`.
+    pub fn last_span(&self) -> Span {
+        self.span
+    }
+
+    /// The position of the token most recently returned by `next`.
+    pub fn last_position(&self) -> Position {
+        self.tokens.position_at(self.span.start)
+    }
+
+    /// The line and column of a byte offset into the input.
+    pub fn position_at(&self, offset: usize) -> Position {
+        self.tokens.position_at(offset)
+    }
+
+    /// The input text covered by `span`.
+    pub fn slice(&self, span: Span) -> &'a str {
+        self.tokens.slice(span)
+    }
 }
 
 #[test]
 fn test_tokenizer_empty() {
     let test_str = "";
-    let tokens: Vec<Token> = Tokens::from_str(test_str).collect();
+    let tokens: Vec<Token> = Tokens::new(test_str).collect::<Result<_, _>>().unwrap();
     assert_eq!(tokens, vec![]);
 }
 
 #[test]
 fn test_tokenizer_1word() {
     let test_str = "hello";
-    let tokens: Vec<Token> = Tokens::from_str(test_str).collect();
-    assert_eq!(tokens, vec![Token::Word("hello".to_string())]);
+    let tokens: Vec<Token> = Tokens::new(test_str).collect::<Result<_, _>>().unwrap();
+    assert_eq!(tokens, vec![Token::Word("hello")]);
 }
 
 #[test]
 fn test_tokenizer_2words() {
     let test_str = "hello world";
-    let tokens: Vec<Token> = Tokens::from_str(test_str).collect();
-    assert_eq!(
-        tokens,
-        vec![
-            Token::Word("hello".to_string()),
-            Token::Word("world".to_string()),
-        ]
-    );
+    let tokens: Vec<Token> = Tokens::new(test_str).collect::<Result<_, _>>().unwrap();
+    assert_eq!(tokens, vec![Token::Word("hello"), Token::Word("world"),]);
 }
 
 #[test]
 fn test_tokenizer_1number() {
     let test_str = "4.2";
-    let tokens: Vec<Token> = Tokens::from_str(test_str).collect();
+    let tokens: Vec<Token> = Tokens::new(test_str).collect::<Result<_, _>>().unwrap();
     assert_eq!(tokens, vec![Token::Number(4.2)]);
 }
 
 #[test]
 fn test_tokenizer_1number_plus() {
     let test_str = "+4.2";
-    let tokens: Vec<Token> = Tokens::from_str(test_str).collect();
+    let tokens: Vec<Token> = Tokens::new(test_str).collect::<Result<_, _>>().unwrap();
     assert_eq!(tokens, vec![Token::Number(4.2)]);
 }
 
 #[test]
 fn test_tokenizer_2numbers() {
     let test_str = ".4 -2";
-    let tokens: Vec<Token> = Tokens::from_str(test_str).collect();
+    let tokens: Vec<Token> = Tokens::new(test_str).collect::<Result<_, _>>().unwrap();
     assert_eq!(tokens, vec![Token::Number(0.4), Token::Number(-2.0)]);
 }
 
 #[test]
 fn test_tokenizer_point() {
     let test_str = "POINT (10 -20)";
-    let tokens: Vec<Token> = Tokens::from_str(test_str).collect();
+    let tokens: Vec<Token> = Tokens::new(test_str).collect::<Result<_, _>>().unwrap();
     assert_eq!(
         tokens,
         vec![
-            Token::Word("POINT".to_string()),
+            Token::Word("POINT"),
             Token::ParenOpen,
             Token::Number(10.0),
             Token::Number(-20.0),
@@ -172,3 +596,86 @@ fn test_tokenizer_point() {
         ]
     );
 }
+
+#[test]
+fn test_tokenizer_exponents() {
+    let test_str = "1e-7 -2.5E+3 .5e2 5. 3e0";
+    let tokens: Vec<Token> = Tokens::new(test_str).collect::<Result<_, _>>().unwrap();
+    assert_eq!(
+        tokens,
+        vec![
+            Token::Number(1e-7),
+            Token::Number(-2500.0),
+            Token::Number(50.0),
+            Token::Number(5.0),
+            Token::Number(3.0),
+        ]
+    );
+}
+
+#[test]
+fn test_tokenizer_non_finite() {
+    let test_str = "NaN -inf +Infinity nan";
+    let tokens: Vec<Token> = Tokens::new(test_str).collect::<Result<_, _>>().unwrap();
+    assert_eq!(tokens.len(), 4);
+    match (tokens[0], tokens[1], tokens[2], tokens[3]) {
+        (Token::Number(a), Token::Number(b), Token::Number(c), Token::Number(d)) => {
+            assert!(a.is_nan());
+            assert_eq!(b, f64::NEG_INFINITY);
+            assert_eq!(c, f64::INFINITY);
+            assert!(d.is_nan());
+        }
+        _ => panic!("expected numbers, found {:?}", tokens),
+    }
+}
+
+#[test]
+fn test_tokenizer_invalid_numbers() {
+    let invalid = [
+        "1-2", "--5", "+", "1.2.3", "-.e", "++5", "1e", "1e+", "2E-x", "1abc", "-nope", ".",
+    ];
+    for test_str in invalid.iter() {
+        let result: Result<Vec<Token>, _> = Tokens::new(test_str).collect();
+        match result {
+            Err(Error::InvalidNumber { ref found, .. }) => assert_eq!(test_str, found),
+            _ => panic!("expected an invalid number error for {:?}", test_str),
+        }
+    }
+}
+
+#[test]
+fn test_tokenizer_punctuation() {
+    let test_str = "EMPTY;POINT";
+    let tokens: Vec<Token> = Tokens::new(test_str).collect::<Result<_, _>>().unwrap();
+    assert_eq!(
+        tokens,
+        vec![Token::Word("EMPTY"), Token::Word(";"), Token::Word("POINT")]
+    );
+}
+
+#[test]
+fn test_tokenizer_spans() {
+    let mut tokens = Tokens::new(" POINT\n(1 2)");
+    let mut spans = vec![];
+    while let Some(token) = tokens.next() {
+        token.unwrap();
+        let span = tokens.last_span();
+        spans.push((span.start, span.end));
+    }
+    assert_eq!(spans, vec![(1, 6), (7, 8), (8, 9), (10, 11), (11, 12)]);
+    assert_eq!(
+        tokens.position_at(8),
+        Position {
+            offset: 8,
+            line: 2,
+            column: 2,
+        }
+    );
+}
+
+#[test]
+fn test_tokenizer_long_input() {
+    let test_str = " ".repeat(1_000_000) + &"x".repeat(1_000_000);
+    let tokens: Vec<Token> = Tokens::new(&test_str).collect::<Result<_, _>>().unwrap();
+    assert_eq!(tokens.len(), 1);
+}
@@ -1,144 +1,318 @@
 extern crate geo;
 
-use types::Coord;
-use types::GeometryCollection;
-use types::LineString;
-use types::MultiLineString;
-use types::MultiPoint;
-use types::MultiPolygon;
-use types::Point;
-use types::Polygon;
-use Geometry;
+use std::fmt;
+use std::io;
+
+use processor::GeometryBuilder;
+use types::Dimension;
+use writer::{write_fmt_with, IoSink};
+use GeometryType;
+use Processor;
 use Wkt;
+use WktFloat;
+use WriterOptions;
 
 /// A trait for converting values to WKT
+///
+/// It can be used as a trait object, as in `Box<dyn ToWkt>`. It is implemented for every type
+/// implementing `ToWktExt`, which is where the `geo` types get it from.
 pub trait ToWkt {
     /// Converts the value of `self` to an instance of WKT
     fn to_wkt(&self) -> Wkt;
+
+    /// Converts the value of `self` to WKT text, written with the default `WriterOptions`
+    fn to_wkt_string(&self) -> String {
+        self.to_wkt().to_string()
+    }
+}
+
+/// The conversions to WKT that are generic over the coordinate type or the sink, which a
+/// `dyn ToWkt` cannot offer.
+pub trait ToWktExt {
+    /// Reports `self` to `processor` the way the parser reports a parsed geometry, with
+    /// coordinates converted to `T`
+    fn process_wkt<T: WktFloat, P: Processor<T>>(&self, processor: &mut P);
+
+    /// Converts the value of `self` to an instance of WKT with coordinates stored as `T`
+    fn to_wkt_as<T: WktFloat>(&self) -> Wkt<T> {
+        let mut builder = GeometryBuilder::new();
+        self.process_wkt(&mut builder);
+        Wkt {
+            items: builder.finish().into_iter().collect(),
+        }
+    }
+
+    /// Writes `self` as WKT into a `fmt::Write` sink, such as a `String`, as it goes, without
+    /// building a `Wkt` or the text first. Fails with the first error the sink returns
+    fn write_wkt_fmt<W: fmt::Write>(&self, sink: W, options: &WriterOptions) -> fmt::Result {
+        write_fmt_with(sink, options, |writer| self.process_wkt::<f64, _>(writer))
+    }
+
+    /// Writes `self` as WKT into an `io::Write` sink, such as a file or socket, as it goes,
+    /// without building a `Wkt`. Fails with the first `io::Error` the sink returns, as it was
+    /// returned
+    fn write_wkt_io<W: io::Write>(&self, sink: W, options: &WriterOptions) -> io::Result<()> {
+        let mut sink = IoSink::new(sink);
+        let result = write_fmt_with(&mut sink, options, |writer| {
+            self.process_wkt::<f64, _>(writer)
+        });
+        sink.into_result(result)
+    }
 }
 
-fn g_point_to_w_coord(g_point: &geo::Point) -> Coord {
-    let geo::Point(coord) = *g_point;
-    let geo::Coordinate { x, y } = coord;
-    Coord {
-        x: x,
-        y: y,
-        z: None,
-        m: None,
+impl<G: ToWktExt + ?Sized> ToWkt for G {
+    fn to_wkt(&self) -> Wkt {
+        self.to_wkt_as()
+    }
+
+    /// Writes the text as it goes, without building a `Wkt` first.
+    fn to_wkt_string(&self) -> String {
+        let mut text = String::new();
+        self.write_wkt_fmt(&mut text, &WriterOptions::default())
+            .expect("writing to a String does not fail");
+        text
     }
 }
 
-fn g_point_to_w_point(g_point: &geo::Point) -> Point {
-    let coord = g_point_to_w_coord(g_point);
-    Point(Some(coord))
+fn g_coord_process<T: WktFloat, P: Processor<T>>(g_coord: &geo::Coordinate, processor: &mut P) {
+    let geo::Coordinate { x, y } = *g_coord;
+    processor.coord(T::from_f64(x), T::from_f64(y), None, None);
 }
 
-fn g_points_to_w_coords(g_points: &Vec<geo::Point>) -> Vec<Coord> {
-    let mut w_points = vec![];
+fn g_point_process_coord<T: WktFloat, P: Processor<T>>(g_point: &geo::Point, processor: &mut P) {
+    let geo::Point(ref coord) = *g_point;
+    g_coord_process(coord, processor);
+}
+
+fn g_point_process<T: WktFloat, P: Processor<T>>(g_point: &geo::Point, processor: &mut P) {
+    processor.geometry_begin(GeometryType::Point, Dimension::XY);
+    g_point_process_coord(g_point, processor);
+    processor.geometry_end();
+}
+
+fn g_line_process<T: WktFloat, P: Processor<T>>(g_line: &geo::LineString, processor: &mut P) {
+    let geo::LineString(ref g_points) = *g_line;
+    processor.geometry_begin(GeometryType::LineString, Dimension::XY);
     for g_point in g_points {
-        w_points.push(g_point_to_w_coord(g_point));
+        g_point_process_coord(g_point, processor);
     }
-    w_points
+    processor.geometry_end();
 }
 
-fn g_line_to_w_line(g_line: &geo::LineString) -> LineString {
-    let &geo::LineString(ref g_points) = g_line;
-    g_points_to_w_line(g_points)
+fn g_ring_process<T: WktFloat, P: Processor<T>>(g_line: &geo::LineString, processor: &mut P) {
+    let geo::LineString(ref g_points) = *g_line;
+    processor.ring_begin();
+    for g_point in g_points {
+        g_point_process_coord(g_point, processor);
+    }
+    processor.ring_end();
 }
 
-fn g_points_to_w_line(g_points: &Vec<geo::Point>) -> LineString {
-    let w_points = g_points_to_w_coords(g_points);
-    LineString(w_points)
+fn g_polygon_process<T: WktFloat, P: Processor<T>>(g_polygon: &geo::Polygon, processor: &mut P) {
+    let geo::Polygon(ref outer_line, ref inner_lines) = *g_polygon;
+    processor.geometry_begin(GeometryType::Polygon, Dimension::XY);
+    g_ring_process(outer_line, processor);
+    for inner_line in inner_lines {
+        g_ring_process(inner_line, processor);
+    }
+    processor.geometry_end();
 }
 
-fn g_lines_to_w_lines(g_lines: &Vec<geo::LineString>) -> Vec<LineString> {
-    let mut w_lines = vec![];
+fn g_mpoint_process<T: WktFloat, P: Processor<T>>(g_mpoint: &geo::MultiPoint, processor: &mut P) {
+    let geo::MultiPoint(ref g_points) = *g_mpoint;
+    processor.geometry_begin(GeometryType::MultiPoint, Dimension::XY);
+    for g_point in g_points {
+        g_point_process(g_point, processor);
+    }
+    processor.geometry_end();
+}
+
+fn g_mline_process<T: WktFloat, P: Processor<T>>(
+    g_mline: &geo::MultiLineString,
+    processor: &mut P,
+) {
+    let geo::MultiLineString(ref g_lines) = *g_mline;
+    processor.geometry_begin(GeometryType::MultiLineString, Dimension::XY);
     for g_line in g_lines {
-        let &geo::LineString(ref g_points) = g_line;
-        w_lines.push(g_points_to_w_line(g_points));
+        g_line_process(g_line, processor);
+    }
+    processor.geometry_end();
+}
+
+fn g_mpolygon_process<T: WktFloat, P: Processor<T>>(
+    g_mpolygon: &geo::MultiPolygon,
+    processor: &mut P,
+) {
+    let geo::MultiPolygon(ref g_polygons) = *g_mpolygon;
+    processor.geometry_begin(GeometryType::MultiPolygon, Dimension::XY);
+    for g_polygon in g_polygons {
+        g_polygon_process(g_polygon, processor);
     }
-    w_lines
+    processor.geometry_end();
 }
 
-fn g_polygon_to_w_polygon(g_polygon: &geo::Polygon) -> Polygon {
-    let &geo::Polygon(ref outer_line, ref inner_lines) = g_polygon;
-    let mut poly_lines = vec![];
+fn g_geocol_process<T: WktFloat, P: Processor<T>>(
+    g_geocol: &geo::GeometryCollection,
+    processor: &mut P,
+) {
+    let geo::GeometryCollection(ref g_geoms) = *g_geocol;
+    processor.geometry_begin(GeometryType::GeometryCollection, Dimension::XY);
+    for g_geom in g_geoms {
+        g_geom_process(g_geom, processor);
+    }
+    processor.geometry_end();
+}
+
+fn g_geom_process<T: WktFloat, P: Processor<T>>(g_geom: &geo::Geometry, processor: &mut P) {
+    match *g_geom {
+        geo::Geometry::Point(ref g_point) => g_point_process(g_point, processor),
 
-    // Outer
-    let &geo::LineString(ref outer_points) = outer_line;
-    poly_lines.push(g_points_to_w_line(outer_points));
+        geo::Geometry::LineString(ref g_line) => g_line_process(g_line, processor),
 
-    // Inner
-    let inner = g_lines_to_w_lines(inner_lines);
-    poly_lines.extend(inner.into_iter());
+        geo::Geometry::Polygon(ref g_polygon) => g_polygon_process(g_polygon, processor),
 
-    Polygon(poly_lines)
+        geo::Geometry::MultiPoint(ref g_mpoint) => g_mpoint_process(g_mpoint, processor),
+
+        geo::Geometry::MultiLineString(ref g_mline) => g_mline_process(g_mline, processor),
+
+        geo::Geometry::MultiPolygon(ref g_mpolygon) => g_mpolygon_process(g_mpolygon, processor),
+
+        geo::Geometry::GeometryCollection(ref g_geocol) => g_geocol_process(g_geocol, processor),
+    }
 }
 
-fn g_mpoint_to_w_mpoint(g_mpoint: &geo::MultiPoint) -> MultiPoint {
-    let &geo::MultiPoint(ref g_points) = g_mpoint;
-    let w_coords = g_points_to_w_coords(g_points);
-    let w_points = w_coords.into_iter().map(|c| Point(Some(c))).collect();
-    MultiPoint(w_points)
+impl ToWktExt for geo::Geometry {
+    fn process_wkt<T: WktFloat, P: Processor<T>>(&self, processor: &mut P) {
+        g_geom_process(self, processor);
+    }
 }
 
-fn g_mline_to_w_mline(g_mline: &geo::MultiLineString) -> MultiLineString {
-    let &geo::MultiLineString(ref g_lines) = g_mline;
-    let w_lines = g_lines_to_w_lines(g_lines);
-    MultiLineString(w_lines)
+/// A coordinate is converted to a `POINT`.
+impl ToWktExt for geo::Coordinate {
+    fn process_wkt<T: WktFloat, P: Processor<T>>(&self, processor: &mut P) {
+        processor.geometry_begin(GeometryType::Point, Dimension::XY);
+        g_coord_process(self, processor);
+        processor.geometry_end();
+    }
 }
 
-fn g_polygons_to_w_polygons(g_polygons: &Vec<geo::Polygon>) -> Vec<Polygon> {
-    let mut w_polygons = vec![];
-    for g_polygon in g_polygons {
-        w_polygons.push(g_polygon_to_w_polygon(g_polygon));
+impl ToWktExt for geo::Point {
+    fn process_wkt<T: WktFloat, P: Processor<T>>(&self, processor: &mut P) {
+        g_point_process(self, processor);
     }
-    w_polygons
 }
 
-fn g_mpolygon_to_w_mpolygon(g_mpolygon: &geo::MultiPolygon) -> MultiPolygon {
-    let &geo::MultiPolygon(ref g_polygons) = g_mpolygon;
-    let w_polygons = g_polygons_to_w_polygons(g_polygons);
-    MultiPolygon(w_polygons)
+impl ToWktExt for geo::LineString {
+    fn process_wkt<T: WktFloat, P: Processor<T>>(&self, processor: &mut P) {
+        g_line_process(self, processor);
+    }
 }
 
-fn g_geocol_to_w_geocol(g_geocol: &geo::GeometryCollection) -> GeometryCollection {
-    let &geo::GeometryCollection(ref g_geoms) = g_geocol;
-    let mut w_geoms = vec![];
-    for g_geom in g_geoms {
-        let w_geom = g_geom_to_w_geom(g_geom);
-        w_geoms.push(w_geom);
+impl ToWktExt for geo::Polygon {
+    fn process_wkt<T: WktFloat, P: Processor<T>>(&self, processor: &mut P) {
+        g_polygon_process(self, processor);
     }
-    GeometryCollection(w_geoms)
 }
 
-fn g_geom_to_w_geom(g_geom: &geo::Geometry) -> Geometry {
-    match g_geom {
-        &geo::Geometry::Point(ref g_point) => g_point_to_w_point(g_point).as_item(),
+impl ToWktExt for geo::MultiPoint {
+    fn process_wkt<T: WktFloat, P: Processor<T>>(&self, processor: &mut P) {
+        g_mpoint_process(self, processor);
+    }
+}
 
-        &geo::Geometry::LineString(ref g_line) => g_line_to_w_line(g_line).as_item(),
+impl ToWktExt for geo::MultiLineString {
+    fn process_wkt<T: WktFloat, P: Processor<T>>(&self, processor: &mut P) {
+        g_mline_process(self, processor);
+    }
+}
+
+impl ToWktExt for geo::MultiPolygon {
+    fn process_wkt<T: WktFloat, P: Processor<T>>(&self, processor: &mut P) {
+        g_mpolygon_process(self, processor);
+    }
+}
 
-        &geo::Geometry::Polygon(ref g_polygon) => g_polygon_to_w_polygon(g_polygon).as_item(),
+impl ToWktExt for geo::GeometryCollection {
+    fn process_wkt<T: WktFloat, P: Processor<T>>(&self, processor: &mut P) {
+        g_geocol_process(self, processor);
+    }
+}
 
-        &geo::Geometry::MultiPoint(ref g_mpoint) => g_mpoint_to_w_mpoint(g_mpoint).as_item(),
+#[cfg(test)]
+mod tests {
+    use super::geo;
+    use super::{ToWkt, ToWktExt};
+    use {Geometry, WriterOptions};
 
-        &geo::Geometry::MultiLineString(ref g_mline) => g_mline_to_w_mline(g_mline).as_item(),
+    fn geometry() -> geo::Geometry {
+        let point = |x, y| geo::Point(geo::Coordinate { x, y });
+        let ring = geo::LineString(vec![point(0., 0.), point(1., 0.), point(0., 0.)]);
+        geo::Geometry::GeometryCollection(geo::GeometryCollection(vec![
+            geo::Geometry::Point(point(1., 2.5)),
+            geo::Geometry::MultiPolygon(geo::MultiPolygon(vec![geo::Polygon(ring, vec![])])),
+        ]))
+    }
 
-        &geo::Geometry::MultiPolygon(ref g_mpolygon) => {
-            g_mpolygon_to_w_mpolygon(g_mpolygon).as_item()
-        }
+    #[test]
+    fn write_geo_geometry() {
+        let expected = "GEOMETRYCOLLECTION (POINT (1 2.5), MULTIPOLYGON (((0 0, 1 0, 0 0))))";
+        let g_geom = geometry();
 
-        &geo::Geometry::GeometryCollection(ref g_geocol) => {
-            g_geocol_to_w_geocol(g_geocol).as_item()
+        let mut text = String::new();
+        g_geom
+            .write_wkt_fmt(&mut text, &WriterOptions::default())
+            .ok()
+            .unwrap();
+        assert_eq!(expected, text);
+
+        let mut bytes = vec![];
+        g_geom
+            .write_wkt_io(&mut bytes, &WriterOptions::default())
+            .ok()
+            .unwrap();
+        assert_eq!(expected.as_bytes(), &bytes[..]);
+
+        let wkt = g_geom.to_wkt();
+        assert_eq!(expected, wkt.to_string());
+        match wkt.items[0] {
+            Geometry::GeometryCollection(ref collection) => assert_eq!(2, collection.0.len()),
+            _ => unreachable!(),
         }
+        assert_eq!(expected, g_geom.to_wkt_string());
     }
-}
 
-impl ToWkt for geo::Geometry {
-    fn to_wkt(&self) -> Wkt {
-        let w_geom = g_geom_to_w_geom(&self);
-        Wkt {
-            items: vec![w_geom],
+    #[test]
+    fn write_geo_types() {
+        let coord = |x, y| geo::Coordinate { x, y };
+        let point = |x, y| geo::Point(coord(x, y));
+        let line = geo::LineString(vec![point(1., 2.), point(3., 4.)]);
+        let ring = geo::LineString(vec![point(0., 0.), point(1., 0.), point(0., 0.)]);
+        let polygon = geo::Polygon(ring, vec![]);
+
+        assert_eq!("POINT (1 2)", coord(1., 2.).to_wkt_string());
+        assert_eq!("POINT (1 2)", point(1., 2.).to_wkt_string());
+        assert_eq!("LINESTRING (1 2, 3 4)", line.to_wkt_string());
+        assert_eq!("POLYGON ((0 0, 1 0, 0 0))", polygon.to_wkt_string());
+        assert_eq!(
+            "MULTIPOINT ((1 2), (3 4))",
+            geo::MultiPoint(vec![point(1., 2.), point(3., 4.)]).to_wkt_string()
+        );
+        assert_eq!(
+            "MULTILINESTRING ((1 2, 3 4))",
+            geo::MultiLineString(vec![line]).to_wkt_string()
+        );
+        assert_eq!(
+            "MULTIPOLYGON (((0 0, 1 0, 0 0)))",
+            geo::MultiPolygon(vec![polygon.clone()]).to_wkt_string()
+        );
+        assert_eq!(
+            "GEOMETRYCOLLECTION EMPTY",
+            geo::GeometryCollection(vec![]).to_wkt_string()
+        );
+
+        match polygon.to_wkt().items[0] {
+            Geometry::Polygon(ref polygon) => assert_eq!(3, polygon.0[0].0.len()),
+            _ => unreachable!(),
         }
     }
 }
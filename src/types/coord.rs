@@ -12,32 +12,128 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::fmt;
 use tokenizer::{PeekableTokens, Token};
-use FromTokens;
-
-#[derive(Default)]
-pub struct Coord {
-    pub x: f64,
-    pub y: f64,
-    pub z: Option<f64>,
-    pub m: Option<f64>,
+use types::Dimension;
+use Error;
+use NonFinite;
+use Processor;
+use WktFloat;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Coord<T: WktFloat = f64> {
+    pub x: T,
+    pub y: T,
+    pub z: Option<T>,
+    pub m: Option<T>,
+}
+
+impl<T: WktFloat> fmt::Display for Coord<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.x, self.y)?;
+        if let Some(ref z) = self.z {
+            write!(f, " {}", z)?;
+        }
+        if let Some(ref m) = self.m {
+            write!(f, " {}", m)?;
+        }
+        Ok(())
+    }
 }
 
-impl FromTokens for Coord {
-    fn from_tokens(tokens: &mut PeekableTokens) -> Result<Self, &'static str> {
-        let x = match tokens.next() {
-            Some(Token::Number(n)) => n,
-            _ => return Err("Expected a number for the X coordinate"),
+impl<T: WktFloat> Coord<T> {
+    /// Reports the coordinate to `processor`.
+    pub(crate) fn process<P: Processor<T>>(&self, processor: &mut P) {
+        processor.coord(
+            self.x.clone(),
+            self.y.clone(),
+            self.z.clone(),
+            self.m.clone(),
+        );
+    }
+
+    /// The dimension implied by which of `z` and `m` are present.
+    pub fn dimension(&self) -> Dimension {
+        match (self.z.is_some(), self.m.is_some()) {
+            (false, false) => Dimension::XY,
+            (true, false) => Dimension::XYZ,
+            (false, true) => Dimension::XYM,
+            (true, true) => Dimension::XYZM,
+        }
+    }
+}
+
+impl<T: WktFloat> Coord<T> {
+    /// Converts the number just read from `tokens`, applying the `NonFinite` policy.
+    fn number_from_tokens(tokens: &PeekableTokens, n: f64) -> Result<T, Error> {
+        let n = T::from_number(n, tokens.slice(tokens.last_span()));
+        if n.is_finite() || tokens.options().non_finite != NonFinite::Reject {
+            return Ok(n);
+        }
+        Err(Error::InvalidNumber {
+            found: tokens.slice(tokens.last_span()).to_string(),
+            position: tokens.last_position(),
+        })
+    }
+}
+
+impl<T: WktFloat> Coord<T> {
+    /// Parses a coordinate with as many values as `dim` requires.
+    pub(crate) fn from_tokens(tokens: &mut PeekableTokens, dim: Dimension) -> Result<Self, Error> {
+        let mut values: [T; 4] = Default::default();
+        match tokens.next()? {
+            Some(Token::Number(n)) => values[0] = Coord::number_from_tokens(tokens, n)?,
+            other => return Err(Error::unexpected("a number", other, tokens.last_position())),
         };
-        let y = match tokens.next() {
-            Some(Token::Number(n)) => n,
-            _ => return Err("Expected a number for the Y coordinate"),
+        let offset = tokens.last_span().start;
+        tokens.add_coord(offset)?;
+        let mut count = 1;
+        while let Some(&Token::Number(n)) = tokens.peek()? {
+            tokens.next()?;
+            let n = Coord::number_from_tokens(tokens, n)?;
+            if count < values.len() {
+                values[count] = n;
+            }
+            count += 1;
+        }
+        if count != dim.size() {
+            return Err(Error::CoordinateArity {
+                expected: dim.size(),
+                found: count,
+                position: tokens.position_at(offset),
+            });
+        }
+        let [x, y, third, fourth] = values;
+        let (z, m) = match dim {
+            Dimension::XY => (None, None),
+            Dimension::XYZ => (Some(third), None),
+            Dimension::XYM => (None, Some(third)),
+            Dimension::XYZM => (Some(third), Some(fourth)),
         };
-        Ok(Coord {
-            x: x,
-            y: y,
+        Ok(Coord { x, y, z, m })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Coord;
+
+    #[test]
+    fn write_coord() {
+        let coord = Coord {
+            x: 10.0,
+            y: -20.5,
             z: None,
             m: None,
-        })
+        };
+        assert_eq!("10 -20.5", coord.to_string());
+
+        let coord = Coord {
+            x: 1.0,
+            y: 2.0,
+            z: Some(3.0),
+            m: Some(4.0),
+        };
+        assert_eq!("1 2 3 4", coord.to_string());
     }
 }
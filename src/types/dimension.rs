@@ -0,0 +1,100 @@
+// Copyright 2015 The GeoRust Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use tokenizer::{PeekableTokens, Token};
+use Error;
+use GeometryType;
+
+/// The coordinate dimension of a geometry, as declared by the ISO `Z`, `M` and `ZM` keywords.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Dimension {
+    XY,
+    XYZ,
+    XYM,
+    XYZM,
+}
+
+impl Dimension {
+    /// Whether coordinates of this dimension carry a Z value.
+    pub fn has_z(self) -> bool {
+        self == Dimension::XYZ || self == Dimension::XYZM
+    }
+
+    /// Whether coordinates of this dimension carry an M value.
+    pub fn has_m(self) -> bool {
+        self == Dimension::XYM || self == Dimension::XYZM
+    }
+
+    /// The number of values in each coordinate.
+    pub fn size(self) -> usize {
+        match self {
+            Dimension::XY => 2,
+            Dimension::XYZ | Dimension::XYM => 3,
+            Dimension::XYZM => 4,
+        }
+    }
+
+    /// The keyword written after the geometry type, e.g. `Z` in `POINT Z (1 2 3)`.
+    pub fn keyword(self) -> Option<&'static str> {
+        match self {
+            Dimension::XY => None,
+            Dimension::XYZ => Some("Z"),
+            Dimension::XYM => Some("M"),
+            Dimension::XYZM => Some("ZM"),
+        }
+    }
+
+    /// Consumes an optional dimension keyword following a geometry type keyword.
+    pub(crate) fn from_tokens(tokens: &mut PeekableTokens) -> Result<Option<Self>, Error> {
+        let dimension = match tokens.peek()? {
+            Some(Token::Word(w)) if w.eq_ignore_ascii_case("Z") => Dimension::XYZ,
+            Some(Token::Word(w)) if w.eq_ignore_ascii_case("M") => Dimension::XYM,
+            Some(Token::Word(w)) if w.eq_ignore_ascii_case("ZM") => Dimension::XYZM,
+            _ => return Ok(None),
+        };
+        tokens.next()?;
+        Ok(Some(dimension))
+    }
+
+    /// Infers the dimension of a geometry of type `geometry_type` that declares none from the
+    /// number of values in its first coordinate, looking ahead in `tokens` without consuming
+    /// anything. As in PostGIS, three values are XYZ and four XYZM; anything else, including
+    /// an empty geometry, is XY.
+    ///
+    /// A collection is always XY, leaving each member to declare or infer its own dimension.
+    pub(crate) fn infer(geometry_type: GeometryType, tokens: &mut PeekableTokens) -> Self {
+        if geometry_type == GeometryType::GeometryCollection {
+            return Dimension::XY;
+        }
+        let mut depth = 0;
+        let mut count = 0;
+        for token in tokens.lookahead() {
+            match token {
+                Ok(Token::Number(_)) => count += 1,
+                _ if count > 0 => break,
+                Ok(Token::ParenOpen) => depth += 1,
+                Ok(Token::ParenClose) if depth <= 1 => break,
+                Ok(Token::ParenClose) => depth -= 1,
+                Ok(Token::Word(_)) if depth == 0 => break,
+                Ok(Token::Comma) | Ok(Token::Word(_)) => (),
+                Err(_) => break,
+            }
+        }
+        match count {
+            3 => Dimension::XYZ,
+            4 => Dimension::XYZM,
+            _ => Dimension::XY,
+        }
+    }
+}
@@ -12,44 +12,124 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
 use tokenizer::{PeekableTokens, Token};
+use types::{from_str_as, Dimension};
+use writer::write_fmt_with;
+use Error;
 use FromTokens;
 use Geometry;
+use GeometryType;
+use Processor;
+use TypeMismatchError;
+use WktFloat;
+use {process_type_dim_and_tokens, type_and_dim_from_word};
+use {WriteWkt, WriterOptions};
 
-#[derive(Default)]
-pub struct GeometryCollection(pub Vec<Geometry>);
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GeometryCollection<T: WktFloat = f64>(pub Vec<Geometry<T>>);
 
-impl GeometryCollection {
-    pub fn as_item(self) -> Geometry {
+impl<T: WktFloat> GeometryCollection<T> {
+    pub fn as_item(self) -> Geometry<T> {
         Geometry::GeometryCollection(self)
     }
+
+    /// Reports the collection to `processor` the way the parser reports a parsed one, with each
+    /// member reporting its own dimension.
+    pub fn process<P: Processor<T>>(&self, processor: &mut P) {
+        processor.geometry_begin(GeometryType::GeometryCollection, Dimension::XY);
+        for geometry in &self.0 {
+            geometry.process(processor);
+        }
+        processor.geometry_end();
+    }
 }
 
-impl FromTokens for GeometryCollection {
-    fn from_tokens(tokens: &mut PeekableTokens) -> Result<Self, &'static str> {
-        let mut items = Vec::new();
+impl<T: WktFloat> WriteWkt for GeometryCollection<T> {
+    fn write_wkt(&self, f: &mut fmt::Formatter, options: &WriterOptions) -> fmt::Result {
+        write_fmt_with(f, options, |writer| self.process(writer))
+    }
+}
 
-        let word = match tokens.next() {
-            Some(Token::Word(w)) => w,
-            _ => return Err("Expected a word in GEOMETRYCOLLECTION"),
-        };
+impl<T: WktFloat> fmt::Display for GeometryCollection<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write_wkt(f, &WriterOptions::default())
+    }
+}
 
-        let item = try!(Geometry::from_word_and_tokens(&*word, tokens));
-        items.push(item);
+impl<T: WktFloat> TryFrom<Geometry<T>> for GeometryCollection<T> {
+    type Error = TypeMismatchError;
 
-        while let Some(&Token::Comma) = tokens.peek() {
-            tokens.next(); // throw away comma
+    fn try_from(geometry: Geometry<T>) -> Result<Self, TypeMismatchError> {
+        match geometry {
+            Geometry::GeometryCollection(geometrycollection) => Ok(geometrycollection),
+            other => Err(TypeMismatchError {
+                expected: GeometryType::GeometryCollection,
+                found: other.geometry_type(),
+            }),
+        }
+    }
+}
 
-            let word = match tokens.next() {
-                Some(Token::Word(w)) => w,
-                _ => return Err("Expected a word in GEOMETRYCOLLECTION"),
-            };
+/// Parses a single geometry, which has to be a `GEOMETRYCOLLECTION`.
+impl<T: WktFloat> FromStr for GeometryCollection<T> {
+    type Err = Error;
 
-            let item = try!(Geometry::from_word_and_tokens(&*word, tokens));
-            items.push(item);
-        }
+    fn from_str(wkt_str: &str) -> Result<Self, Error> {
+        from_str_as(wkt_str)
+    }
+}
 
-        Ok(GeometryCollection(items))
+impl<T: WktFloat> GeometryCollection<T> {
+    /// Parses one member geometry. If the collection itself declared a dimension, each member
+    /// has that dimension and may only declare the same one.
+    fn member_from_tokens<P: Processor<T>>(
+        tokens: &mut PeekableTokens,
+        dim: Dimension,
+        processor: &mut P,
+    ) -> Result<(), Error> {
+        let word = match tokens.next()? {
+            Some(Token::Word(w)) => w,
+            other => {
+                return Err(Error::unexpected(
+                    "a geometry type",
+                    other,
+                    tokens.last_position(),
+                ))
+            }
+        };
+        let offset = tokens.last_span().start;
+        let (geometry_type, member_dim) = type_and_dim_from_word(word, tokens)?;
+        let member_dim = match member_dim {
+            Some(member_dim) if dim != Dimension::XY && member_dim != dim => {
+                return Err(Error::DimensionMismatch {
+                    expected: dim,
+                    found: member_dim,
+                    position: tokens.position_at(offset),
+                })
+            }
+            Some(member_dim) => member_dim,
+            None if dim != Dimension::XY => dim,
+            None => Dimension::infer(geometry_type, tokens),
+        };
+        process_type_dim_and_tokens(geometry_type, member_dim, offset, tokens, processor)
+    }
+}
+
+impl<T: WktFloat> FromTokens<T> for GeometryCollection<T> {
+    fn from_tokens<P: Processor<T>>(
+        tokens: &mut PeekableTokens,
+        dim: Dimension,
+        processor: &mut P,
+    ) -> Result<(), Error> {
+        Self::comma_many(
+            GeometryCollection::member_from_tokens,
+            tokens,
+            dim,
+            processor,
+        )
     }
 }
 
@@ -60,7 +140,7 @@ mod tests {
 
     #[test]
     fn basic_geometrycollection() {
-        let mut wkt = Wkt::from_str("GEOMETRYCOLLECTION (POINT (8 4)))")
+        let mut wkt = Wkt::from_str("GEOMETRYCOLLECTION (POINT (8 4))")
             .ok()
             .unwrap();
         assert_eq!(1, wkt.items.len());
@@ -69,5 +149,35 @@ mod tests {
             _ => unreachable!(),
         };
         assert_eq!(1, items.len());
+
+        Wkt::from_str("GEOMETRYCOLLECTION (POINT (8 4)))")
+            .err()
+            .unwrap();
+    }
+
+    #[test]
+    fn geometrycollection_dimensions() {
+        Wkt::from_str("GEOMETRYCOLLECTION Z (POINT Z (1 2 3), LINESTRING Z (1 2 3, 4 5 6))")
+            .ok()
+            .unwrap();
+        Wkt::from_str("GEOMETRYCOLLECTION (POINT Z (1 2 3), POINT M (1 2 3))")
+            .ok()
+            .unwrap();
+        Wkt::from_str("GEOMETRYCOLLECTION Z (POINT (1 2))")
+            .err()
+            .unwrap();
+    }
+
+    #[test]
+    fn write_geometrycollection() {
+        let input = "GEOMETRYCOLLECTION (POINT (8 4), LINESTRING EMPTY, \
+                     GEOMETRYCOLLECTION (POLYGON ((1 2, 3 4, 5 6, 1 2))))";
+        let wkt = Wkt::from_str(input).ok().unwrap();
+        assert_eq!(input, wkt.to_string());
+
+        assert_eq!(
+            "GEOMETRYCOLLECTION EMPTY",
+            GeometryCollection::<f64>(vec![]).to_string()
+        );
     }
 }
@@ -12,24 +12,105 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
 use tokenizer::PeekableTokens;
 use types::coord::Coord;
+use types::{coord_dimension, from_str_as, Dimension};
+use writer::write_fmt_with;
+use Error;
 use FromTokens;
 use Geometry;
+use GeometryType;
+use Processor;
+use TypeMismatchError;
+use WktFloat;
+use {WriteWkt, WriterOptions};
 
-#[derive(Default)]
-pub struct LineString(pub Vec<Coord>);
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LineString<T: WktFloat = f64>(pub Vec<Coord<T>>);
 
-impl LineString {
-    pub fn as_item(self) -> Geometry {
+impl<T: WktFloat> LineString<T> {
+    pub fn as_item(self) -> Geometry<T> {
         Geometry::LineString(self)
     }
+
+    /// Reports the line string to `processor` the way the parser reports a parsed one, with the
+    /// dimension of its first coordinate.
+    pub fn process<P: Processor<T>>(&self, processor: &mut P) {
+        self.process_as(coord_dimension(self.0.first()), processor);
+    }
+
+    /// Like `process`, but reporting `dim` as the dimension, as for a member of a
+    /// multi-line string.
+    pub(crate) fn process_as<P: Processor<T>>(&self, dim: Dimension, processor: &mut P) {
+        processor.geometry_begin(GeometryType::LineString, dim);
+        for coord in &self.0 {
+            coord.process(processor);
+        }
+        processor.geometry_end();
+    }
+
+    /// Reports the line string to `processor` as a ring of a polygon.
+    pub(crate) fn process_ring<P: Processor<T>>(&self, processor: &mut P) {
+        processor.ring_begin();
+        for coord in &self.0 {
+            coord.process(processor);
+        }
+        processor.ring_end();
+    }
+}
+
+impl<T: WktFloat> WriteWkt for LineString<T> {
+    fn write_wkt(&self, f: &mut fmt::Formatter, options: &WriterOptions) -> fmt::Result {
+        write_fmt_with(f, options, |writer| self.process(writer))
+    }
 }
 
-impl FromTokens for LineString {
-    fn from_tokens(tokens: &mut PeekableTokens) -> Result<Self, &'static str> {
-        let result = FromTokens::comma_many(<Coord as FromTokens>::from_tokens, tokens);
-        result.map(|vec| LineString(vec))
+impl<T: WktFloat> fmt::Display for LineString<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write_wkt(f, &WriterOptions::default())
+    }
+}
+
+impl<T: WktFloat> TryFrom<Geometry<T>> for LineString<T> {
+    type Error = TypeMismatchError;
+
+    fn try_from(geometry: Geometry<T>) -> Result<Self, TypeMismatchError> {
+        match geometry {
+            Geometry::LineString(linestring) => Ok(linestring),
+            other => Err(TypeMismatchError {
+                expected: GeometryType::LineString,
+                found: other.geometry_type(),
+            }),
+        }
+    }
+}
+
+/// Parses a single geometry, which has to be a `LINESTRING`.
+impl<T: WktFloat> FromStr for LineString<T> {
+    type Err = Error;
+
+    fn from_str(wkt_str: &str) -> Result<Self, Error> {
+        from_str_as(wkt_str)
+    }
+}
+
+impl<T: WktFloat> FromTokens<T> for LineString<T> {
+    fn from_tokens<P: Processor<T>>(
+        tokens: &mut PeekableTokens,
+        dim: Dimension,
+        processor: &mut P,
+    ) -> Result<(), Error> {
+        let coord_from_tokens = |tokens: &mut PeekableTokens, dim, processor: &mut P| {
+            let start = tokens.next_offset();
+            let coord = Coord::from_tokens(tokens, dim)?;
+            processor.coord(coord.x, coord.y, coord.z, coord.m);
+            processor.span(tokens.span_from(start));
+            Ok(())
+        };
+        Self::comma_many(coord_from_tokens, tokens, dim, processor)
     }
 }
 
@@ -58,4 +139,34 @@ mod tests {
         assert_eq!(None, coords[1].z);
         assert_eq!(None, coords[1].m);
     }
+
+    #[test]
+    fn linestring_m() {
+        let mut wkt = Wkt::from_str("LINESTRING M (10 -20 1, 0 -0.5 2)")
+            .ok()
+            .unwrap();
+        let coords = match wkt.items.pop().unwrap() {
+            Geometry::LineString(LineString(coords)) => coords,
+            _ => unreachable!(),
+        };
+        assert_eq!(None, coords[0].z);
+        assert_eq!(Some(1.0), coords[0].m);
+        assert_eq!(None, coords[1].z);
+        assert_eq!(Some(2.0), coords[1].m);
+
+        Wkt::from_str("LINESTRING M (10 -20 1, 0 -0.5)")
+            .err()
+            .unwrap();
+        Wkt::from_str("LINESTRING (10 -20, 0 -0.5 2)")
+            .err()
+            .unwrap();
+    }
+
+    #[test]
+    fn write_linestring() {
+        let wkt = Wkt::from_str("LINESTRING (10 -20, -0 -0.5)").ok().unwrap();
+        assert_eq!("LINESTRING (10 -20, -0 -0.5)", wkt.to_string());
+
+        assert_eq!("LINESTRING EMPTY", LineString::<f64>(vec![]).to_string());
+    }
 }
@@ -12,12 +12,15 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::ascii::AsciiExt;
-use std::default::Default;
-
-use tokenizer::{PeekableTokens, Token};
+use std::convert::TryFrom;
+use Error;
+use Geometry;
+use Position;
+use TypeMismatchError;
+use WktFloat;
 
 pub use self::coord::Coord;
+pub use self::dimension::Dimension;
 pub use self::geometrycollection::GeometryCollection;
 pub use self::linestring::LineString;
 pub use self::multilinestring::MultiLineString;
@@ -27,6 +30,7 @@ pub use self::point::Point;
 pub use self::polygon::Polygon;
 
 mod coord;
+mod dimension;
 mod geometrycollection;
 mod linestring;
 mod multilinestring;
@@ -35,45 +39,26 @@ mod multipolygon;
 mod point;
 mod polygon;
 
-trait FromTokens: Sized + Default {
-    fn from_tokens(tokens: &mut PeekableTokens) -> Result<Self, &'static str>;
-
-    fn from_tokens_with_parens(tokens: &mut PeekableTokens) -> Result<Self, &'static str> {
-        match tokens.next() {
-            Some(Token::ParenOpen) => (),
-            Some(Token::Word(ref s)) if s.to_ascii_uppercase() == "EMPTY" => {
-                return Ok(Default::default())
-            }
-            _ => return Err("Missing open parenthesis for type"),
-        };
-        let result = FromTokens::from_tokens(tokens);
-        match tokens.next() {
-            Some(Token::ParenClose) => (),
-            _ => return Err("Missing closing parenthesis for type"),
-        };
-        result
-    }
-
-    fn comma_many<F>(f: F, tokens: &mut PeekableTokens) -> Result<Vec<Self>, &'static str>
-    where
-        F: Fn(&mut PeekableTokens) -> Result<Self, &'static str>,
-    {
-        let mut items = Vec::new();
-
-        match f(tokens) {
-            Ok(i) => items.push(i),
-            Err(s) => return Err(s),
-        };
-
-        while let Some(&Token::Comma) = tokens.peek() {
-            tokens.next(); // throw away comma
+/// The dimension of a geometry whose first coordinate is `first`, with an empty geometry being
+/// two-dimensional.
+fn coord_dimension<T: WktFloat>(first: Option<&Coord<T>>) -> Dimension {
+    first.map_or(Dimension::XY, Coord::dimension)
+}
 
-            match f(tokens) {
-                Ok(i) => items.push(i),
-                Err(s) => return Err(s),
-            };
+/// Parses a single geometry of the type `G`, for the `FromStr` implementations of the geometry
+/// types. A geometry of another type is reported at the position of its keyword.
+fn from_str_as<T, G>(wkt_str: &str) -> Result<G, Error>
+where
+    T: WktFloat,
+    G: TryFrom<Geometry<T>, Error = TypeMismatchError>,
+{
+    let geometry = wkt_str.parse::<Geometry<T>>()?;
+    G::try_from(geometry).map_err(|err| {
+        let offset = wkt_str.len() - wkt_str.trim_start().len();
+        Error::TypeMismatch {
+            expected: err.expected,
+            found: err.found,
+            position: Position::from_offset(wkt_str, offset),
         }
-
-        Ok(items)
-    }
+    })
 }
@@ -12,25 +12,93 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
 use tokenizer::PeekableTokens;
 use types::linestring::LineString;
+use types::{coord_dimension, from_str_as, Dimension};
+use writer::write_fmt_with;
+use Error;
 use FromTokens;
 use Geometry;
+use GeometryType;
+use Processor;
+use TypeMismatchError;
+use WktFloat;
+use {WriteWkt, WriterOptions};
 
-#[derive(Default)]
-pub struct MultiLineString(pub Vec<LineString>);
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MultiLineString<T: WktFloat = f64>(pub Vec<LineString<T>>);
 
-impl MultiLineString {
-    pub fn as_item(self) -> Geometry {
+impl<T: WktFloat> MultiLineString<T> {
+    pub fn as_item(self) -> Geometry<T> {
         Geometry::MultiLineString(self)
     }
+
+    /// Reports the multi-line string to `processor` the way the parser reports a parsed one,
+    /// with the dimension of its first coordinate.
+    pub fn process<P: Processor<T>>(&self, processor: &mut P) {
+        let first = self.0.iter().filter_map(|line| line.0.first()).next();
+        let dim = coord_dimension(first);
+        processor.geometry_begin(GeometryType::MultiLineString, dim);
+        for line in &self.0 {
+            line.process_as(dim, processor);
+        }
+        processor.geometry_end();
+    }
+}
+
+impl<T: WktFloat> WriteWkt for MultiLineString<T> {
+    fn write_wkt(&self, f: &mut fmt::Formatter, options: &WriterOptions) -> fmt::Result {
+        write_fmt_with(f, options, |writer| self.process(writer))
+    }
+}
+
+impl<T: WktFloat> fmt::Display for MultiLineString<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write_wkt(f, &WriterOptions::default())
+    }
 }
 
-impl FromTokens for MultiLineString {
-    fn from_tokens(tokens: &mut PeekableTokens) -> Result<Self, &'static str> {
-        let result =
-            FromTokens::comma_many(<LineString as FromTokens>::from_tokens_with_parens, tokens);
-        result.map(|vec| MultiLineString(vec))
+impl<T: WktFloat> TryFrom<Geometry<T>> for MultiLineString<T> {
+    type Error = TypeMismatchError;
+
+    fn try_from(geometry: Geometry<T>) -> Result<Self, TypeMismatchError> {
+        match geometry {
+            Geometry::MultiLineString(multilinestring) => Ok(multilinestring),
+            other => Err(TypeMismatchError {
+                expected: GeometryType::MultiLineString,
+                found: other.geometry_type(),
+            }),
+        }
+    }
+}
+
+/// Parses a single geometry, which has to be a `MULTILINESTRING`.
+impl<T: WktFloat> FromStr for MultiLineString<T> {
+    type Err = Error;
+
+    fn from_str(wkt_str: &str) -> Result<Self, Error> {
+        from_str_as(wkt_str)
+    }
+}
+
+impl<T: WktFloat> FromTokens<T> for MultiLineString<T> {
+    fn from_tokens<P: Processor<T>>(
+        tokens: &mut PeekableTokens,
+        dim: Dimension,
+        processor: &mut P,
+    ) -> Result<(), Error> {
+        let line_string_from_tokens = |tokens: &mut PeekableTokens, dim, processor: &mut P| {
+            let start = tokens.next_offset();
+            processor.geometry_begin(GeometryType::LineString, dim);
+            <LineString<T> as FromTokens<T>>::from_tokens_with_parens(tokens, dim, processor)?;
+            processor.geometry_end();
+            processor.span(tokens.span_from(start));
+            Ok(())
+        };
+        Self::comma_many(line_string_from_tokens, tokens, dim, processor)
     }
 }
 
@@ -51,4 +119,20 @@ mod tests {
         };
         assert_eq!(2, lines.len());
     }
+
+    #[test]
+    fn write_multilinestring() {
+        let wkt = Wkt::from_str("MULTILINESTRING ((8 4, -3 0), EMPTY, (4 0, 6 -10))")
+            .ok()
+            .unwrap();
+        assert_eq!(
+            "MULTILINESTRING ((8 4, -3 0), EMPTY, (4 0, 6 -10))",
+            wkt.to_string()
+        );
+
+        assert_eq!(
+            "MULTILINESTRING EMPTY",
+            MultiLineString::<f64>(vec![]).to_string()
+        );
+    }
 }
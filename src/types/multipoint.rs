@@ -12,31 +12,116 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use tokenizer::PeekableTokens;
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+use tokenizer::{PeekableTokens, Token};
 use types::point::Point;
+use types::{coord_dimension, from_str_as, Dimension};
+use writer::write_fmt_with;
+use Error;
 use FromTokens;
 use Geometry;
+use GeometryType;
+use Processor;
+use TypeMismatchError;
+use WktFloat;
+use {WriteWkt, WriterOptions};
 
-#[derive(Default)]
-pub struct MultiPoint(pub Vec<Point>);
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MultiPoint<T: WktFloat = f64>(pub Vec<Point<T>>);
 
-impl MultiPoint {
-    pub fn as_item(self) -> Geometry {
+impl<T: WktFloat> MultiPoint<T> {
+    pub fn as_item(self) -> Geometry<T> {
         Geometry::MultiPoint(self)
     }
+
+    /// Reports the multi-point to `processor` the way the parser reports a parsed one, with the
+    /// dimension of its first coordinate.
+    pub fn process<P: Processor<T>>(&self, processor: &mut P) {
+        let first = self.0.iter().filter_map(|point| point.0.as_ref()).next();
+        let dim = coord_dimension(first);
+        processor.geometry_begin(GeometryType::MultiPoint, dim);
+        for point in &self.0 {
+            point.process_as(dim, processor);
+        }
+        processor.geometry_end();
+    }
+
+    /// Parses a member point, which may or may not be wrapped in parentheses.
+    fn point_from_tokens<P: Processor<T>>(
+        tokens: &mut PeekableTokens,
+        dim: Dimension,
+        processor: &mut P,
+    ) -> Result<(), Error> {
+        let depth = tokens.paren_depth();
+        let bare = matches!(tokens.peek()?, Some(&Token::Number(_)));
+        let start = tokens.next_offset();
+        processor.geometry_begin(GeometryType::Point, dim);
+        if bare {
+            if let Err(err) = <Point<T> as FromTokens<T>>::from_tokens(tokens, dim, processor) {
+                // Recover here, so that the point is still ended.
+                tokens.recover(err)?;
+                tokens.skip_to(depth);
+            }
+        } else {
+            <Point<T> as FromTokens<T>>::from_tokens_with_parens(tokens, dim, processor)?;
+        }
+        processor.geometry_end();
+        processor.span(tokens.span_from(start));
+        Ok(())
+    }
 }
 
-impl FromTokens for MultiPoint {
-    fn from_tokens(tokens: &mut PeekableTokens) -> Result<Self, &'static str> {
-        let result = FromTokens::comma_many(<Point as FromTokens>::from_tokens_with_parens, tokens);
-        result.map(|vec| MultiPoint(vec))
+impl<T: WktFloat> WriteWkt for MultiPoint<T> {
+    fn write_wkt(&self, f: &mut fmt::Formatter, options: &WriterOptions) -> fmt::Result {
+        write_fmt_with(f, options, |writer| self.process(writer))
+    }
+}
+
+impl<T: WktFloat> fmt::Display for MultiPoint<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write_wkt(f, &WriterOptions::default())
+    }
+}
+
+impl<T: WktFloat> TryFrom<Geometry<T>> for MultiPoint<T> {
+    type Error = TypeMismatchError;
+
+    fn try_from(geometry: Geometry<T>) -> Result<Self, TypeMismatchError> {
+        match geometry {
+            Geometry::MultiPoint(multipoint) => Ok(multipoint),
+            other => Err(TypeMismatchError {
+                expected: GeometryType::MultiPoint,
+                found: other.geometry_type(),
+            }),
+        }
+    }
+}
+
+/// Parses a single geometry, which has to be a `MULTIPOINT`.
+impl<T: WktFloat> FromStr for MultiPoint<T> {
+    type Err = Error;
+
+    fn from_str(wkt_str: &str) -> Result<Self, Error> {
+        from_str_as(wkt_str)
+    }
+}
+
+impl<T: WktFloat> FromTokens<T> for MultiPoint<T> {
+    fn from_tokens<P: Processor<T>>(
+        tokens: &mut PeekableTokens,
+        dim: Dimension,
+        processor: &mut P,
+    ) -> Result<(), Error> {
+        Self::comma_many(MultiPoint::point_from_tokens, tokens, dim, processor)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::MultiPoint;
-    use {Geometry, Wkt};
+    use {Geometry, Wkt, WriteWkt, WriterOptions};
 
     #[test]
     fn basic_multipoint() {
@@ -48,4 +133,38 @@ mod tests {
         };
         assert_eq!(2, points.len());
     }
+
+    #[test]
+    fn multipoint_without_parens() {
+        let mut wkt = Wkt::from_str("MULTIPOINT (8 4, 4 0)").ok().unwrap();
+        let points = match wkt.items.pop().unwrap() {
+            Geometry::MultiPoint(MultiPoint(points)) => points,
+            _ => unreachable!(),
+        };
+        assert_eq!(2, points.len());
+
+        let wkt = Wkt::from_str("MULTIPOINT Z ((8 4 1), 4 0 2, EMPTY)")
+            .ok()
+            .unwrap();
+        assert_eq!("MULTIPOINT Z ((8 4 1), (4 0 2), EMPTY)", wkt.to_string());
+
+        let options = WriterOptions {
+            multipoint_parens: false,
+            ..Default::default()
+        };
+        assert_eq!(
+            "MULTIPOINT Z (8 4 1, 4 0 2, EMPTY)",
+            wkt.with_options(&options).to_string()
+        );
+    }
+
+    #[test]
+    fn write_multipoint() {
+        let wkt = Wkt::from_str("MULTIPOINT ((8 4), EMPTY, (4 0))")
+            .ok()
+            .unwrap();
+        assert_eq!("MULTIPOINT ((8 4), EMPTY, (4 0))", wkt.to_string());
+
+        assert_eq!("MULTIPOINT EMPTY", MultiPoint::<f64>(vec![]).to_string());
+    }
 }
@@ -12,25 +12,98 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
 use tokenizer::PeekableTokens;
 use types::polygon::Polygon;
+use types::{coord_dimension, from_str_as, Dimension};
+use writer::write_fmt_with;
+use Error;
 use FromTokens;
 use Geometry;
+use GeometryType;
+use Processor;
+use TypeMismatchError;
+use WktFloat;
+use {WriteWkt, WriterOptions};
 
-#[derive(Default)]
-pub struct MultiPolygon(pub Vec<Polygon>);
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MultiPolygon<T: WktFloat = f64>(pub Vec<Polygon<T>>);
 
-impl MultiPolygon {
-    pub fn as_item(self) -> Geometry {
+impl<T: WktFloat> MultiPolygon<T> {
+    pub fn as_item(self) -> Geometry<T> {
         Geometry::MultiPolygon(self)
     }
+
+    /// Reports the multi-polygon to `processor` the way the parser reports a parsed one, with
+    /// the dimension of its first coordinate.
+    pub fn process<P: Processor<T>>(&self, processor: &mut P) {
+        let first = self
+            .0
+            .iter()
+            .flat_map(|polygon| polygon.0.iter())
+            .filter_map(|ring| ring.0.first())
+            .next();
+        let dim = coord_dimension(first);
+        processor.geometry_begin(GeometryType::MultiPolygon, dim);
+        for polygon in &self.0 {
+            polygon.process_as(dim, processor);
+        }
+        processor.geometry_end();
+    }
+}
+
+impl<T: WktFloat> WriteWkt for MultiPolygon<T> {
+    fn write_wkt(&self, f: &mut fmt::Formatter, options: &WriterOptions) -> fmt::Result {
+        write_fmt_with(f, options, |writer| self.process(writer))
+    }
+}
+
+impl<T: WktFloat> fmt::Display for MultiPolygon<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write_wkt(f, &WriterOptions::default())
+    }
 }
 
-impl FromTokens for MultiPolygon {
-    fn from_tokens(tokens: &mut PeekableTokens) -> Result<Self, &'static str> {
-        let result =
-            FromTokens::comma_many(<Polygon as FromTokens>::from_tokens_with_parens, tokens);
-        result.map(|vec| MultiPolygon(vec))
+impl<T: WktFloat> TryFrom<Geometry<T>> for MultiPolygon<T> {
+    type Error = TypeMismatchError;
+
+    fn try_from(geometry: Geometry<T>) -> Result<Self, TypeMismatchError> {
+        match geometry {
+            Geometry::MultiPolygon(multipolygon) => Ok(multipolygon),
+            other => Err(TypeMismatchError {
+                expected: GeometryType::MultiPolygon,
+                found: other.geometry_type(),
+            }),
+        }
+    }
+}
+
+/// Parses a single geometry, which has to be a `MULTIPOLYGON`.
+impl<T: WktFloat> FromStr for MultiPolygon<T> {
+    type Err = Error;
+
+    fn from_str(wkt_str: &str) -> Result<Self, Error> {
+        from_str_as(wkt_str)
+    }
+}
+
+impl<T: WktFloat> FromTokens<T> for MultiPolygon<T> {
+    fn from_tokens<P: Processor<T>>(
+        tokens: &mut PeekableTokens,
+        dim: Dimension,
+        processor: &mut P,
+    ) -> Result<(), Error> {
+        let polygon_from_tokens = |tokens: &mut PeekableTokens, dim, processor: &mut P| {
+            let start = tokens.next_offset();
+            processor.geometry_begin(GeometryType::Polygon, dim);
+            <Polygon<T> as FromTokens<T>>::from_tokens_with_parens(tokens, dim, processor)?;
+            processor.geometry_end();
+            processor.span(tokens.span_from(start));
+            Ok(())
+        };
+        Self::comma_many(polygon_from_tokens, tokens, dim, processor)
     }
 }
 
@@ -51,4 +124,20 @@ mod tests {
         };
         assert_eq!(2, polygons.len());
     }
+
+    #[test]
+    fn write_multipolygon() {
+        let wkt = Wkt::from_str("MULTIPOLYGON (((8 4)), EMPTY, ((4 0), (1 1)))")
+            .ok()
+            .unwrap();
+        assert_eq!(
+            "MULTIPOLYGON (((8 4)), EMPTY, ((4 0), (1 1)))",
+            wkt.to_string()
+        );
+
+        assert_eq!(
+            "MULTIPOLYGON EMPTY",
+            MultiPolygon::<f64>(vec![]).to_string()
+        );
+    }
 }
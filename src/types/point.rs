@@ -12,38 +12,114 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::convert::TryFrom;
 use std::fmt;
+use std::str::FromStr;
 use tokenizer::PeekableTokens;
 use types::coord::Coord;
+use types::{coord_dimension, from_str_as, Dimension};
+use writer::write_fmt_with;
+use Error;
 use FromTokens;
 use Geometry;
+use GeometryType;
+use NonFinite;
+use Processor;
+use TypeMismatchError;
+use WktFloat;
+use {WriteWkt, WriterOptions};
 
-#[derive(Default)]
-pub struct Point(pub Option<Coord>);
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Point<T: WktFloat = f64>(pub Option<Coord<T>>);
 
-impl Point {
-    pub fn as_item(self) -> Geometry {
+impl<T: WktFloat> Point<T> {
+    pub fn as_item(self) -> Geometry<T> {
         Geometry::Point(self)
     }
+
+    /// Reports the point to `processor` the way the parser reports a parsed one, with the
+    /// dimension of its coordinate.
+    pub fn process<P: Processor<T>>(&self, processor: &mut P) {
+        self.process_as(coord_dimension(self.0.as_ref()), processor);
+    }
+
+    /// Like `process`, but reporting `dim` as the dimension, as for a member of a multi-point.
+    pub(crate) fn process_as<P: Processor<T>>(&self, dim: Dimension, processor: &mut P) {
+        processor.geometry_begin(GeometryType::Point, dim);
+        if let Some(ref coord) = self.0 {
+            coord.process(processor);
+        }
+        processor.geometry_end();
+    }
+}
+
+impl<T: WktFloat> WriteWkt for Point<T> {
+    fn write_wkt(&self, f: &mut fmt::Formatter, options: &WriterOptions) -> fmt::Result {
+        write_fmt_with(f, options, |writer| self.process(writer))
+    }
+}
+
+impl<T: WktFloat> fmt::Display for Point<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write_wkt(f, &WriterOptions::default())
+    }
 }
 
-impl fmt::Display for Point {
-    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        f.write_str("POINT")
+impl<T: WktFloat> TryFrom<Geometry<T>> for Point<T> {
+    type Error = TypeMismatchError;
+
+    fn try_from(geometry: Geometry<T>) -> Result<Self, TypeMismatchError> {
+        match geometry {
+            Geometry::Point(point) => Ok(point),
+            other => Err(TypeMismatchError {
+                expected: GeometryType::Point,
+                found: other.geometry_type(),
+            }),
+        }
     }
 }
 
-impl FromTokens for Point {
-    fn from_tokens(tokens: &mut PeekableTokens) -> Result<Self, &'static str> {
-        let result = <Coord as FromTokens>::from_tokens(tokens);
-        result.map(|coord| Point(Some(coord)))
+/// Parses a single geometry, which has to be a `POINT`.
+impl<T: WktFloat> FromStr for Point<T> {
+    type Err = Error;
+
+    fn from_str(wkt_str: &str) -> Result<Self, Error> {
+        from_str_as(wkt_str)
+    }
+}
+
+impl<T: WktFloat> FromTokens<T> for Point<T> {
+    fn from_tokens<P: Processor<T>>(
+        tokens: &mut PeekableTokens,
+        dim: Dimension,
+        processor: &mut P,
+    ) -> Result<(), Error> {
+        let start = tokens.next_offset();
+        let coord: Coord<T> = Coord::from_tokens(tokens, dim)?;
+        let all_nan = coord.x.is_nan()
+            && coord.y.is_nan()
+            && coord.z.iter().all(WktFloat::is_nan)
+            && coord.m.iter().all(WktFloat::is_nan);
+        if all_nan && tokens.options().non_finite == NonFinite::Empty {
+            return Ok(());
+        }
+        processor.coord(coord.x, coord.y, coord.z, coord.m);
+        processor.span(tokens.span_from(start));
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::Point;
-    use {Geometry, Wkt};
+    use std::str::FromStr;
+    use types::{Coord, MultiPoint};
+    use {Error, Geometry, NonFinite, ParseOptions, Parser, Wkt};
+
+    fn parse_with(wkt_str: &str, options: &ParseOptions) -> Result<Wkt, Error> {
+        let parser = Parser::with_options(options.clone());
+        parser.parse(wkt_str).map(|parsed| parsed.wkt)
+    }
 
     #[test]
     fn basic_point() {
@@ -75,11 +151,138 @@ mod tests {
         assert_eq!(None, coord.m);
     }
 
+    #[test]
+    fn point_with_dimensions() {
+        let mut wkt = Wkt::from_str("POINT Z (10 -20 40)").ok().unwrap();
+        let coord = match wkt.items.pop().unwrap() {
+            Geometry::Point(Point(Some(coord))) => coord,
+            _ => unreachable!(),
+        };
+        assert_eq!(Some(40.0), coord.z);
+        assert_eq!(None, coord.m);
+
+        let mut wkt = Wkt::from_str("POINT m (10 -20 7)").ok().unwrap();
+        let coord = match wkt.items.pop().unwrap() {
+            Geometry::Point(Point(Some(coord))) => coord,
+            _ => unreachable!(),
+        };
+        assert_eq!(None, coord.z);
+        assert_eq!(Some(7.0), coord.m);
+
+        let mut wkt = Wkt::from_str("POINT ZM (10 -20 40 7)").ok().unwrap();
+        let coord = match wkt.items.pop().unwrap() {
+            Geometry::Point(Point(Some(coord))) => coord,
+            _ => unreachable!(),
+        };
+        assert_eq!(Some(40.0), coord.z);
+        assert_eq!(Some(7.0), coord.m);
+
+        let mut wkt = Wkt::from_str("POINT Z EMPTY").ok().unwrap();
+        match wkt.items.pop().unwrap() {
+            Geometry::Point(Point(None)) => (),
+            _ => unreachable!(),
+        };
+    }
+
+    #[test]
+    fn invalid_points_with_dimensions() {
+        Wkt::from_str("POINT Z (10 -20)").err().unwrap();
+        Wkt::from_str("POINT Z (10 -20 40 7)").err().unwrap();
+        Wkt::from_str("POINT ZM (10 -20 40)").err().unwrap();
+    }
+
+    #[test]
+    fn non_finite_points() {
+        Wkt::from_str("POINT (NaN 1)").err().unwrap();
+        Wkt::from_str("POINT (1 -Infinity)").err().unwrap();
+
+        let options = ParseOptions {
+            non_finite: NonFinite::Accept,
+            ..Default::default()
+        };
+        let mut wkt = parse_with("POINT (NaN -inf)", &options).ok().unwrap();
+        let coord = match wkt.items.pop().unwrap() {
+            Geometry::Point(Point(Some(coord))) => coord,
+            _ => unreachable!(),
+        };
+        assert!(coord.x.is_nan());
+        assert_eq!(f64::NEG_INFINITY, coord.y);
+
+        let options = ParseOptions {
+            non_finite: NonFinite::Empty,
+            ..Default::default()
+        };
+        let mut wkt = parse_with("POINT (NaN NaN)", &options).ok().unwrap();
+        match wkt.items.pop().unwrap() {
+            Geometry::Point(Point(None)) => (),
+            _ => unreachable!(),
+        };
+        let mut wkt = parse_with("MULTIPOINT (1 2, NaN NaN, (nan nan))", &options)
+            .ok()
+            .unwrap();
+        match wkt.items.pop().unwrap() {
+            Geometry::MultiPoint(MultiPoint(points)) => {
+                assert_eq!(points[0].0.as_ref().map(|c| c.x), Some(1.0));
+                assert_eq!(points[1], Point(None));
+                assert_eq!(points[2], Point(None));
+            }
+            _ => unreachable!(),
+        };
+        let mut wkt = parse_with("POINT (NaN 1)", &options).ok().unwrap();
+        match wkt.items.pop().unwrap() {
+            Geometry::Point(Point(Some(coord))) => assert!(coord.x.is_nan()),
+            _ => unreachable!(),
+        };
+    }
+
+    #[test]
+    fn f32_point() {
+        let mut wkt = Wkt::<f32>::from_str("POINT (0.1 -2.5e3)").ok().unwrap();
+        let point = match wkt.items.pop().unwrap() {
+            Geometry::Point(point) => point,
+            _ => unreachable!(),
+        };
+        assert_eq!(
+            Point(Some(Coord {
+                x: 0.1f32,
+                y: -2500.0,
+                z: None,
+                m: None,
+            })),
+            point
+        );
+        assert_eq!("POINT (0.1 -2500)", point.to_string());
+
+        // Finite as an `f64`, but not as an `f32`.
+        Wkt::<f32>::from_str("POINT (1 1e39)").err().unwrap();
+    }
+
     #[test]
     fn invalid_points() {
         Wkt::from_str("POINT ()").err().unwrap();
         Wkt::from_str("POINT (10)").err().unwrap();
         Wkt::from_str("POINT 10").err().unwrap();
-        Wkt::from_str("POINT (10 -20 40)").err().unwrap();
+        Wkt::from_str("POINT (10 -20 40 50 60)").err().unwrap();
+    }
+
+    #[test]
+    fn write_point() {
+        let point = Point(Some(Coord {
+            x: 10.0,
+            y: -20.0,
+            z: None,
+            m: None,
+        }));
+        assert_eq!("POINT (10 -20)", point.to_string());
+
+        let point = Point(Some(Coord {
+            x: 10.0,
+            y: -20.0,
+            z: Some(5.5),
+            m: None,
+        }));
+        assert_eq!("POINT Z (10 -20 5.5)", point.to_string());
+
+        assert_eq!("POINT EMPTY", Point::<f64>(None).to_string());
     }
 }
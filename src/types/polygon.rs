@@ -12,25 +12,98 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
 use tokenizer::PeekableTokens;
 use types::linestring::LineString;
+use types::{coord_dimension, from_str_as, Dimension};
+use writer::write_fmt_with;
+use Error;
 use FromTokens;
 use Geometry;
+use GeometryType;
+use Processor;
+use TypeMismatchError;
+use WktFloat;
+use {WriteWkt, WriterOptions};
 
-#[derive(Default)]
-pub struct Polygon(pub Vec<LineString>);
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Polygon<T: WktFloat = f64>(pub Vec<LineString<T>>);
 
-impl Polygon {
-    pub fn as_item(self) -> Geometry {
+impl<T: WktFloat> Polygon<T> {
+    pub fn as_item(self) -> Geometry<T> {
         Geometry::Polygon(self)
     }
+
+    /// Reports the polygon to `processor` the way the parser reports a parsed one, with the
+    /// dimension of its first coordinate.
+    pub fn process<P: Processor<T>>(&self, processor: &mut P) {
+        let first = self.0.iter().filter_map(|ring| ring.0.first()).next();
+        self.process_as(coord_dimension(first), processor);
+    }
+
+    /// Like `process`, but reporting `dim` as the dimension, as for a member of a
+    /// multi-polygon.
+    pub(crate) fn process_as<P: Processor<T>>(&self, dim: Dimension, processor: &mut P) {
+        processor.geometry_begin(GeometryType::Polygon, dim);
+        for ring in &self.0 {
+            ring.process_ring(processor);
+        }
+        processor.geometry_end();
+    }
+}
+
+impl<T: WktFloat> WriteWkt for Polygon<T> {
+    fn write_wkt(&self, f: &mut fmt::Formatter, options: &WriterOptions) -> fmt::Result {
+        write_fmt_with(f, options, |writer| self.process(writer))
+    }
+}
+
+impl<T: WktFloat> fmt::Display for Polygon<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write_wkt(f, &WriterOptions::default())
+    }
+}
+
+impl<T: WktFloat> TryFrom<Geometry<T>> for Polygon<T> {
+    type Error = TypeMismatchError;
+
+    fn try_from(geometry: Geometry<T>) -> Result<Self, TypeMismatchError> {
+        match geometry {
+            Geometry::Polygon(polygon) => Ok(polygon),
+            other => Err(TypeMismatchError {
+                expected: GeometryType::Polygon,
+                found: other.geometry_type(),
+            }),
+        }
+    }
+}
+
+/// Parses a single geometry, which has to be a `POLYGON`.
+impl<T: WktFloat> FromStr for Polygon<T> {
+    type Err = Error;
+
+    fn from_str(wkt_str: &str) -> Result<Self, Error> {
+        from_str_as(wkt_str)
+    }
 }
 
-impl FromTokens for Polygon {
-    fn from_tokens(tokens: &mut PeekableTokens) -> Result<Self, &'static str> {
-        let result =
-            FromTokens::comma_many(<LineString as FromTokens>::from_tokens_with_parens, tokens);
-        result.map(|vec| Polygon(vec))
+impl<T: WktFloat> FromTokens<T> for Polygon<T> {
+    fn from_tokens<P: Processor<T>>(
+        tokens: &mut PeekableTokens,
+        dim: Dimension,
+        processor: &mut P,
+    ) -> Result<(), Error> {
+        let ring_from_tokens = |tokens: &mut PeekableTokens, dim, processor: &mut P| {
+            let start = tokens.next_offset();
+            processor.ring_begin();
+            <LineString<T> as FromTokens<T>>::from_tokens_with_parens(tokens, dim, processor)?;
+            processor.ring_end();
+            processor.span(tokens.span_from(start));
+            Ok(())
+        };
+        Self::comma_many(ring_from_tokens, tokens, dim, processor)
     }
 }
 
@@ -51,4 +124,36 @@ mod tests {
         };
         assert_eq!(2, lines.len());
     }
+
+    #[test]
+    fn polygon_zm() {
+        let mut wkt = Wkt::from_str("POLYGON ZM ((8 4 1 2, 4 0 1 2, 0 4 1 2, 8 4 1 2))")
+            .ok()
+            .unwrap();
+        let lines = match wkt.items.pop().unwrap() {
+            Geometry::Polygon(Polygon(lines)) => lines,
+            _ => unreachable!(),
+        };
+        assert!(lines[0]
+            .0
+            .iter()
+            .all(|c| c.z == Some(1.0) && c.m == Some(2.0)));
+
+        Wkt::from_str("POLYGON ZM ((8 4 1 2, 4 0 1, 0 4 1 2, 8 4 1 2))")
+            .err()
+            .unwrap();
+    }
+
+    #[test]
+    fn write_polygon() {
+        let wkt = Wkt::from_str("POLYGON ((8 4, 4 0, 0 4, 8 4), (7 3, 4 1, 1 4, 7 3))")
+            .ok()
+            .unwrap();
+        assert_eq!(
+            "POLYGON ((8 4, 4 0, 0 4, 8 4), (7 3, 4 1, 1 4, 7 3))",
+            wkt.to_string()
+        );
+
+        assert_eq!("POLYGON EMPTY", Polygon::<f64>(vec![]).to_string());
+    }
 }
@@ -0,0 +1,736 @@
+// Copyright 2015 The GeoRust Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+use std::fmt::Write;
+use std::io;
+
+use types::Dimension;
+use GeometryType;
+use Processor;
+use WktFloat;
+
+/// Settings controlling how WKT is written.
+///
+/// The `Display` implementations of the WKT types use `WriterOptions::default()`; use
+/// `WriteWkt::with_options` to write with other settings.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WriterOptions {
+    /// Whether `MULTIPOINT` members are wrapped in parentheses, as in `MULTIPOINT ((1 2), (3 4))`,
+    /// rather than written bare, as in `MULTIPOINT (1 2, 3 4)`. Defaults to `true`.
+    pub multipoint_parens: bool,
+    /// The number of decimal places each number is written with, or `None` for writing it as
+    /// `number_format` says, by default with Rust's `Display` output. That parses back to the
+    /// same value but is not always short: it never has an exponent, so `1e300` is written
+    /// with all 301 digits. Defaults to `None`.
+    pub precision: Option<usize>,
+    /// Whether zeros at the end of the fractional part of a number are dropped, along with the
+    /// decimal point if nothing is left after it, so that `1.500` is written as `1.5` and
+    /// `2.000` as `2`. Mostly useful with `precision`. Defaults to `false`.
+    pub trim_trailing_zeros: bool,
+    /// How numbers are written when `precision` is `None`. Defaults to
+    /// `NumberFormat::Display`.
+    pub number_format: NumberFormat,
+    /// The case keywords such as `POINT`, `Z` and `EMPTY` are written in. Defaults to
+    /// `KeywordCase::Upper`.
+    pub keyword_case: KeywordCase,
+    /// Whether a space separates a keyword from the paren following it, as in `POINT (1 2)`,
+    /// rather than `POINT(1 2)`. Defaults to `true`.
+    pub space_after_keyword: bool,
+    /// Whether a space follows each comma, as in `(1 2, 3 4)`, rather than `(1 2,3 4)`.
+    /// Defaults to `true`.
+    pub space_after_comma: bool,
+    /// The number of spaces each level of nesting is indented by, or `None` for writing each
+    /// geometry on a single line. When set, the members of `GEOMETRYCOLLECTION`,
+    /// `MULTILINESTRING` and `MULTIPOLYGON` values and the rings of polygons are each written on
+    /// a line of their own, as is the paren closing them. Defaults to `None`.
+    pub indent: Option<usize>,
+    /// The width that lines written with `indent` are wrapped at, after the comma following a
+    /// coordinate or `MULTIPOINT` member, or `None` for no wrapping. A line can still be wider
+    /// when there is no comma to wrap it at. Defaults to `None`.
+    pub max_line_width: Option<usize>,
+}
+
+/// The ways of writing numbers without a set precision, see `WriterOptions::number_format`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum NumberFormat {
+    /// With Rust's `Display` output: the fewest digits that parse back to the same value,
+    /// written out in full without an exponent.
+    Display,
+    /// With the Ryu algorithm, which is faster, see `WktFloat::write_ryu`.
+    #[cfg(feature = "ryu")]
+    Ryu,
+}
+
+/// The case of the keywords written, see `WriterOptions::keyword_case`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeywordCase {
+    Upper,
+    Lower,
+}
+
+impl Default for WriterOptions {
+    fn default() -> Self {
+        WriterOptions {
+            multipoint_parens: true,
+            precision: None,
+            trim_trailing_zeros: false,
+            number_format: NumberFormat::Display,
+            keyword_case: KeywordCase::Upper,
+            space_after_keyword: true,
+            space_after_comma: true,
+            indent: None,
+            max_line_width: None,
+        }
+    }
+}
+
+/// Writes the geometries reported to it as WKT into a `fmt::Write` sink, such as a `String`,
+/// as the events arrive, one geometry per line or, when pretty-printing with
+/// `WriterOptions::indent`, over as many lines as it takes.
+///
+/// The `WriteWkt` implementations write through a `WktWriter`, fed by the `process` methods of
+/// the geometry types. It can also be fed by `Parser::process`, to reformat WKT without
+/// building any geometries, or by `ToWkt::process_wkt`.
+///
+/// Writing stops at the first error returned by the sink, which `finish` then returns.
+pub struct WktWriter<'a, W: fmt::Write> {
+    sink: W,
+    options: &'a WriterOptions,
+    /// The geometries and rings begun but not yet ended, innermost last.
+    stack: Vec<Level>,
+    /// How many outermost geometries have been begun.
+    geometries: usize,
+    /// The line being written when pretty-printing, held back until it ends so that it can be
+    /// wrapped.
+    line: String,
+    /// How many levels of nesting the line is indented by.
+    line_indent: usize,
+    /// Where the line can be wrapped: just after the last comma between coordinates.
+    wrap_at: Option<usize>,
+    result: fmt::Result,
+}
+
+/// A geometry or ring being written.
+struct Level {
+    /// The type of the geometry, or `None` for a ring.
+    kind: Option<GeometryType>,
+    /// Whether the keyword was written, rather than just the body as for multi-geometry
+    /// members.
+    keyword: bool,
+    /// Whether the body is wrapped in parentheses, which only bare `MULTIPOINT` members are not.
+    parens: bool,
+    /// How many coordinates, rings or members have been written.
+    children: usize,
+    /// Whether each child is written on a line of its own, when pretty-printing.
+    block: bool,
+}
+
+impl<'a, W: fmt::Write> WktWriter<'a, W> {
+    pub fn new(sink: W, options: &'a WriterOptions) -> Self {
+        WktWriter {
+            sink,
+            options,
+            stack: Vec::new(),
+            geometries: 0,
+            line: String::new(),
+            line_indent: 0,
+            wrap_at: None,
+            result: Ok(()),
+        }
+    }
+
+    /// Returns the sink, or the first error writing to it.
+    pub fn finish(mut self) -> Result<W, fmt::Error> {
+        self.write(|writer| writer.sink.write_str(&writer.line));
+        self.result.map(|_| self.sink)
+    }
+
+    /// Runs `write` unless writing already failed.
+    fn write<F: FnOnce(&mut Self) -> fmt::Result>(&mut self, write: F) {
+        if self.result.is_ok() {
+            self.result = write(self);
+        }
+    }
+
+    /// Writes `s`, holding it back in the line when pretty-printing.
+    fn put(&mut self, s: &str) -> fmt::Result {
+        if self.options.indent.is_none() {
+            return self.sink.write_str(s);
+        }
+        self.line.push_str(s);
+        self.wrap()
+    }
+
+    /// Writes the line held back and begins the next, indented by `depth` levels.
+    fn newline(&mut self, depth: usize) -> fmt::Result {
+        self.sink.write_str(&self.line)?;
+        self.sink.write_str("\n")?;
+        self.line.clear();
+        let indent = self.options.indent.unwrap_or(0);
+        for _ in 0..depth * indent {
+            self.line.push(' ');
+        }
+        self.line_indent = depth;
+        self.wrap_at = None;
+        Ok(())
+    }
+
+    /// Wraps the line held back at the last comma if it got wider than the maximum width,
+    /// indenting the rest one level more than the line.
+    fn wrap(&mut self) -> fmt::Result {
+        let at = match (self.options.max_line_width, self.wrap_at) {
+            (Some(width), Some(at)) if self.line.len() > width => at,
+            _ => return Ok(()),
+        };
+        let rest = self.line.split_off(at);
+        let depth = self.line_indent;
+        self.newline(depth + 1)?;
+        self.line_indent = depth;
+        self.line.push_str(rest.trim_start_matches(' '));
+        Ok(())
+    }
+
+    /// Writes `keyword`, given in uppercase, in the configured case.
+    fn write_keyword(&mut self, keyword: &str) -> fmt::Result {
+        match self.options.keyword_case {
+            KeywordCase::Upper => self.put(keyword),
+            KeywordCase::Lower => {
+                for c in keyword.chars() {
+                    self.put(c.to_ascii_lowercase().encode_utf8(&mut [0; 4]))?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Writes `n` with the configured precision, trimming trailing zeros if configured to.
+    fn write_number<T: WktFloat>(&mut self, n: &T) -> fmt::Result {
+        if self.options.indent.is_none() {
+            return format_number(&mut self.sink, self.options, n);
+        }
+        format_number(&mut self.line, self.options, n)?;
+        self.wrap()
+    }
+
+    /// Writes what goes before the next child of the innermost level: the opening paren
+    /// before the first, and a comma before the others.
+    fn child_begin(&mut self) -> fmt::Result {
+        let (first, keyword, parens, block) = match self.stack.last_mut() {
+            Some(level) => {
+                level.children += 1;
+                (
+                    level.children == 1,
+                    level.keyword,
+                    level.parens,
+                    level.block,
+                )
+            }
+            None => return Ok(()),
+        };
+        let depth = self.stack.len();
+        if !first {
+            self.put(",")?;
+            if block {
+                return self.newline(depth);
+            }
+            if self.options.indent.is_some() {
+                self.wrap_at = Some(self.line.len());
+            }
+            if self.options.space_after_comma {
+                self.put(" ")?;
+            }
+            return Ok(());
+        }
+        if !parens {
+            return Ok(());
+        }
+        if keyword && self.options.space_after_keyword {
+            self.put(" ")?;
+        }
+        self.put("(")?;
+        if block {
+            self.newline(depth)?;
+        }
+        Ok(())
+    }
+
+    fn level_end(&mut self) -> fmt::Result {
+        match self.stack.pop() {
+            Some(ref level) if level.children == 0 => {
+                if level.keyword {
+                    self.put(" ")?;
+                }
+                self.write_keyword("EMPTY")
+            }
+            Some(ref level) if level.block => {
+                let depth = self.stack.len();
+                self.newline(depth)?;
+                self.put(")")
+            }
+            Some(ref level) if level.parens => self.put(")"),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl<'a, T: WktFloat, W: fmt::Write> Processor<T> for WktWriter<'a, W> {
+    fn geometry_begin(&mut self, kind: GeometryType, dim: Dimension) {
+        self.write(|writer| {
+            let (keyword, parens) = match writer.stack.last().map(|level| level.kind) {
+                None => {
+                    writer.geometries += 1;
+                    if writer.geometries > 1 {
+                        if writer.options.indent.is_some() {
+                            writer.newline(0)?;
+                        } else {
+                            writer.sink.write_str("\n")?;
+                        }
+                    }
+                    (true, true)
+                }
+                Some(Some(GeometryType::GeometryCollection)) => (true, true),
+                Some(Some(GeometryType::MultiPoint)) => (false, writer.options.multipoint_parens),
+                Some(_) => (false, true),
+            };
+            writer.child_begin()?;
+            if keyword {
+                writer.write_keyword(kind.keyword())?;
+                if let Some(tag) = dim.keyword() {
+                    writer.put(" ")?;
+                    writer.write_keyword(tag)?;
+                }
+            }
+            let block = writer.options.indent.is_some()
+                && matches!(
+                    kind,
+                    GeometryType::GeometryCollection
+                        | GeometryType::MultiLineString
+                        | GeometryType::MultiPolygon
+                        | GeometryType::Polygon
+                );
+            writer.stack.push(Level {
+                kind: Some(kind),
+                keyword,
+                parens,
+                children: 0,
+                block,
+            });
+            Ok(())
+        });
+    }
+
+    fn coord(&mut self, x: T, y: T, z: Option<T>, m: Option<T>) {
+        self.write(|writer| {
+            writer.child_begin()?;
+            writer.write_number(&x)?;
+            writer.put(" ")?;
+            writer.write_number(&y)?;
+            for value in z.iter().chain(m.iter()) {
+                writer.put(" ")?;
+                writer.write_number(value)?;
+            }
+            Ok(())
+        });
+    }
+
+    fn ring_begin(&mut self) {
+        self.write(|writer| {
+            writer.child_begin()?;
+            writer.stack.push(Level {
+                kind: None,
+                keyword: false,
+                parens: true,
+                children: 0,
+                block: false,
+            });
+            Ok(())
+        });
+    }
+
+    fn ring_end(&mut self) {
+        self.write(Self::level_end);
+    }
+
+    fn geometry_end(&mut self) {
+        self.write(Self::level_end);
+    }
+}
+
+/// Writes `n` into `sink` with the precision and format in `options`, trimming trailing zeros
+/// if they say to.
+fn format_number<S: fmt::Write, T: WktFloat>(
+    sink: &mut S,
+    options: &WriterOptions,
+    n: &T,
+) -> fmt::Result {
+    if options.trim_trailing_zeros {
+        let mut sink = TrimZeros {
+            sink,
+            fraction: false,
+            point: false,
+            zeros: 0,
+        };
+        return format_untrimmed(&mut sink, options, n);
+    }
+    format_untrimmed(sink, options, n)
+}
+
+fn format_untrimmed<S: fmt::Write, T: WktFloat>(
+    sink: &mut S,
+    options: &WriterOptions,
+    n: &T,
+) -> fmt::Result {
+    match (options.precision, options.number_format) {
+        (Some(precision), _) => write!(sink, "{:.*}", precision, n),
+        (None, NumberFormat::Display) => write!(sink, "{}", n),
+        #[cfg(feature = "ryu")]
+        (None, NumberFormat::Ryu) => n.write_ryu(sink),
+    }
+}
+
+/// Passes a number through to `sink`, holding back the decimal point and the zeros following
+/// it until something else follows them, so that those ending the number are dropped. Those
+/// before an exponent are kept.
+struct TrimZeros<'a, W: 'a> {
+    sink: &'a mut W,
+    /// Whether the decimal point has been seen.
+    fraction: bool,
+    /// Whether the decimal point is being held back.
+    point: bool,
+    /// How many zeros are being held back.
+    zeros: usize,
+}
+
+impl<'a, W: fmt::Write> fmt::Write for TrimZeros<'a, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            if !self.fraction {
+                if c == '.' {
+                    self.fraction = true;
+                    self.point = true;
+                } else {
+                    self.sink.write_char(c)?;
+                }
+            } else if c == '0' {
+                self.zeros += 1;
+            } else {
+                if self.point {
+                    self.sink.write_char('.')?;
+                    self.point = false;
+                }
+                for _ in 0..self.zeros {
+                    self.sink.write_char('0')?;
+                }
+                self.zeros = 0;
+                self.sink.write_char(c)?;
+                if c == 'e' || c == 'E' {
+                    self.fraction = false;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Lets a `WktWriter` write into an `io::Write` sink, keeping the error that a `fmt::Error`
+/// stands for.
+pub(crate) struct IoSink<W: io::Write> {
+    sink: W,
+    error: Option<io::Error>,
+}
+
+impl<W: io::Write> IoSink<W> {
+    pub(crate) fn new(sink: W) -> Self {
+        IoSink { sink, error: None }
+    }
+
+    /// Converts the result of writing into `self` to the result of writing into the sink.
+    pub(crate) fn into_result(self, result: fmt::Result) -> io::Result<()> {
+        match (result, self.error) {
+            (Ok(()), _) => Ok(()),
+            (Err(_), Some(err)) => Err(err),
+            (Err(_), None) => Err(io::Error::other("formatter error")),
+        }
+    }
+}
+
+impl<W: io::Write> fmt::Write for IoSink<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.sink.write_all(s.as_bytes()).map_err(|err| {
+            self.error = Some(err);
+            fmt::Error
+        })
+    }
+}
+
+/// Feeds a `WktWriter` writing into `sink` to `process`.
+pub(crate) fn write_fmt_with<W, F>(sink: W, options: &WriterOptions, process: F) -> fmt::Result
+where
+    W: fmt::Write,
+    F: FnOnce(&mut WktWriter<W>),
+{
+    let mut writer = WktWriter::new(sink, options);
+    process(&mut writer);
+    writer.finish().map(|_| ())
+}
+
+/// A value that can be written as WKT.
+pub trait WriteWkt {
+    /// Writes `self` as WKT according to `options`.
+    fn write_wkt(&self, f: &mut fmt::Formatter, options: &WriterOptions) -> fmt::Result;
+
+    /// Returns a value whose `Display` implementation writes `self` according to `options`.
+    fn with_options<'a>(&'a self, options: &'a WriterOptions) -> Formatted<'a, Self> {
+        Formatted {
+            value: self,
+            options,
+        }
+    }
+
+    /// Writes `self` as WKT into a `fmt::Write` sink, such as a `String`, as it goes, without
+    /// building the text first. Fails with the first error the sink returns.
+    fn write_wkt_fmt<W: fmt::Write>(&self, mut sink: W, options: &WriterOptions) -> fmt::Result {
+        write!(sink, "{}", self.with_options(options))
+    }
+
+    /// Writes `self` as WKT into an `io::Write` sink, such as a file or socket, as it goes.
+    /// Fails with the first `io::Error` the sink returns, as it was returned. Wrap unbuffered
+    /// sinks in a `BufWriter`, since the text is written in many small pieces.
+    fn write_wkt_io<W: io::Write>(&self, sink: W, options: &WriterOptions) -> io::Result<()> {
+        let mut sink = IoSink::new(sink);
+        let result = write!(sink, "{}", self.with_options(options));
+        sink.into_result(result)
+    }
+}
+
+/// Displays a value as WKT with specific `WriterOptions`, see `WriteWkt::with_options`.
+pub struct Formatted<'a, T: 'a + ?Sized> {
+    value: &'a T,
+    options: &'a WriterOptions,
+}
+
+impl<'a, T: WriteWkt + ?Sized> fmt::Display for Formatted<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.value.write_wkt(f, self.options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{KeywordCase, WktWriter, WriteWkt, WriterOptions};
+    use std::io;
+    use std::str::FromStr;
+    use {Geometry, Lossless, Wkt};
+
+    #[test]
+    fn writer_options() {
+        let wkt = Wkt::from_str(
+            "GEOMETRYCOLLECTION (POINT Z (1.25 -2 0.1), LINESTRING EMPTY, \
+             MULTIPOINT ((1 2), (3.5 4)))",
+        )
+        .ok()
+        .unwrap();
+        let write = |options: &WriterOptions| wkt.with_options(options).to_string();
+
+        assert_eq!(
+            "GEOMETRYCOLLECTION (POINT Z (1.25 -2 0.1), LINESTRING EMPTY, \
+             MULTIPOINT ((1 2), (3.5 4)))",
+            write(&WriterOptions::default())
+        );
+
+        let options = WriterOptions {
+            precision: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(
+            "GEOMETRYCOLLECTION (POINT Z (1.250 -2.000 0.100), LINESTRING EMPTY, \
+             MULTIPOINT ((1.000 2.000), (3.500 4.000)))",
+            write(&options)
+        );
+
+        let options = WriterOptions {
+            precision: Some(1),
+            trim_trailing_zeros: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            "GEOMETRYCOLLECTION (POINT Z (1.2 -2 0.1), LINESTRING EMPTY, \
+             MULTIPOINT ((1 2), (3.5 4)))",
+            write(&options)
+        );
+
+        let options = WriterOptions {
+            keyword_case: KeywordCase::Lower,
+            space_after_keyword: false,
+            space_after_comma: false,
+            ..Default::default()
+        };
+        assert_eq!(
+            "geometrycollection(point z(1.25 -2 0.1),linestring empty,\
+             multipoint((1 2),(3.5 4)))",
+            write(&options)
+        );
+        let written = write(&options);
+        assert_eq!(wkt, Wkt::from_str(&written).ok().unwrap());
+    }
+
+    #[test]
+    fn writer_options_numbers() {
+        let options = WriterOptions {
+            trim_trailing_zeros: true,
+            ..Default::default()
+        };
+        let wkt = Wkt::<Lossless>::from_str("POINT (2.50 1e10)").ok().unwrap();
+        assert_eq!("POINT (2.5 1e10)", wkt.with_options(&options).to_string());
+
+        let options = WriterOptions {
+            precision: Some(0),
+            trim_trailing_zeros: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            "POINT (2 10000000000)",
+            wkt.with_options(&options).to_string()
+        );
+
+        let wkt = Wkt::from_str("POINT (100 0.000001)").ok().unwrap();
+        assert_eq!("POINT (100 0)", wkt.with_options(&options).to_string());
+        assert_eq!("POINT (100 0.000001)", wkt.to_string());
+
+        #[cfg(feature = "ryu")]
+        {
+            let options = WriterOptions {
+                number_format: ::NumberFormat::Ryu,
+                ..Default::default()
+            };
+            assert_eq!("POINT (100 1e-6)", wkt.with_options(&options).to_string());
+        }
+    }
+
+    #[test]
+    fn pretty_print() {
+        let input = "GEOMETRYCOLLECTION (POINT (1 2), \
+                     MULTIPOLYGON (((0 0, 10 0, 10 10, 0 0), (1 1, 2 1, 1 1)), EMPTY), \
+                     GEOMETRYCOLLECTION EMPTY, MULTIPOINT ((1 2), (3 4)), \
+                     LINESTRING (100 200, 300 400, 500 600, 700 800))";
+        let wkt = Wkt::from_str(input).ok().unwrap();
+        let options = WriterOptions {
+            indent: Some(2),
+            ..Default::default()
+        };
+        let written = wkt.with_options(&options).to_string();
+        assert_eq!(
+            "GEOMETRYCOLLECTION (\n\
+             \x20 POINT (1 2),\n\
+             \x20 MULTIPOLYGON (\n\
+             \x20   (\n\
+             \x20     (0 0, 10 0, 10 10, 0 0),\n\
+             \x20     (1 1, 2 1, 1 1)\n\
+             \x20   ),\n\
+             \x20   EMPTY\n\
+             \x20 ),\n\
+             \x20 GEOMETRYCOLLECTION EMPTY,\n\
+             \x20 MULTIPOINT ((1 2), (3 4)),\n\
+             \x20 LINESTRING (100 200, 300 400, 500 600, 700 800)\n\
+             )",
+            written
+        );
+        assert_eq!(wkt, Wkt::from_str(&written).ok().unwrap());
+
+        let options = WriterOptions {
+            indent: Some(4),
+            max_line_width: Some(24),
+            ..Default::default()
+        };
+        let written = wkt.with_options(&options).to_string();
+        assert_eq!(
+            "GEOMETRYCOLLECTION (\n\
+             \x20   POINT (1 2),\n\
+             \x20   MULTIPOLYGON (\n\
+             \x20       (\n\
+             \x20           (0 0, 10 0,\n\
+             \x20               10 10,\n\
+             \x20               0 0),\n\
+             \x20           (1 1, 2 1,\n\
+             \x20               1 1)\n\
+             \x20       ),\n\
+             \x20       EMPTY\n\
+             \x20   ),\n\
+             \x20   GEOMETRYCOLLECTION EMPTY,\n\
+             \x20   MULTIPOINT ((1 2),\n\
+             \x20       (3 4)),\n\
+             \x20   LINESTRING (100 200,\n\
+             \x20       300 400,\n\
+             \x20       500 600,\n\
+             \x20       700 800)\n\
+             )",
+            written
+        );
+        assert_eq!(wkt, Wkt::from_str(&written).ok().unwrap());
+    }
+
+    #[test]
+    fn stream_wkt() {
+        let options = WriterOptions {
+            space_after_comma: false,
+            ..Default::default()
+        };
+        let geometry = Geometry::<f64>::from_str("MULTIPOINT (1 2, EMPTY)")
+            .ok()
+            .unwrap();
+        let mut text = String::from("> ");
+        geometry.write_wkt_fmt(&mut text, &options).ok().unwrap();
+        assert_eq!("> MULTIPOINT ((1 2),EMPTY)", text);
+
+        let mut bytes = vec![];
+        geometry.write_wkt_io(&mut bytes, &options).ok().unwrap();
+        assert_eq!(b"MULTIPOINT ((1 2),EMPTY)", &bytes[..]);
+
+        // Reformatting straight from the parser, without building any geometries.
+        let input = "point z(1 2 3)\n  linestring m EMPTY  polygon((0 0,1 0,0 0))";
+        let mut writer = WktWriter::new(String::new(), &options);
+        for result in ::Parser::new()
+            .many(::Separator::Whitespace)
+            .iter::<f64>(input)
+        {
+            result.ok().unwrap().process(&mut writer);
+        }
+        let mut text = String::new();
+        let mut reformatter = WktWriter::new(&mut text, &options);
+        ::Parser::new()
+            .process::<f64, _>("point z(1 2 3)", &mut reformatter)
+            .ok()
+            .unwrap();
+        reformatter.finish().ok().unwrap();
+        assert_eq!("POINT Z (1 2 3)", text);
+        assert_eq!(
+            "POINT Z (1 2 3)\nLINESTRING EMPTY\nPOLYGON ((0 0,1 0,0 0))",
+            writer.finish().ok().unwrap()
+        );
+
+        struct Full;
+        impl io::Write for Full {
+            fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+                Err(io::Error::new(io::ErrorKind::WriteZero, "full"))
+            }
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+        let err = geometry.write_wkt_io(Full, &options).err().unwrap();
+        assert_eq!(io::ErrorKind::WriteZero, err.kind());
+    }
+}
@@ -0,0 +1,193 @@
+// Copyright 2015 The GeoRust Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+extern crate wkt;
+
+use wkt::tokenizer::{PeekableTokens, Span, Token, TokenKind, Tokens};
+use wkt::{Error, Limit, ParseOptions};
+
+fn tokenize(input: &str) -> Vec<Token<'_>> {
+    Tokens::new(input).collect::<Result<_, _>>().unwrap()
+}
+
+fn spans(input: &str) -> Vec<(usize, usize)> {
+    let mut tokens = Tokens::new(input);
+    let mut spans = vec![];
+    while tokens.next().is_some() {
+        let span = tokens.last_span();
+        spans.push((span.start, span.end));
+    }
+    spans
+}
+
+#[test]
+fn whitespace() {
+    assert_eq!(Vec::<Token>::new(), tokenize(" \t\r\n"));
+    assert_eq!(
+        vec![Token::Word("a"), Token::Word("b"), Token::Word("c")],
+        tokenize("\r\na\tb \n c\n")
+    );
+    assert_eq!(vec![(2, 3), (4, 5)], spans("\r\na\tb  "));
+
+    // Other whitespace is not skipped, and stands on its own like any other unknown character.
+    assert_eq!(
+        vec![Token::Word("a"), Token::Word("\u{a0}"), Token::Word("b")],
+        tokenize("a\u{a0}b")
+    );
+
+    // So does NUL, rather than ending the input.
+    assert_eq!(
+        vec![
+            Token::Word("POINT"),
+            Token::Word("\0"),
+            Token::ParenOpen,
+            Token::Number(1.0),
+            Token::Number(2.0),
+            Token::ParenClose
+        ],
+        tokenize("POINT\0(1 2)")
+    );
+}
+
+#[test]
+fn numbers() {
+    assert_eq!(
+        vec![
+            Token::Number(4.2),
+            Token::Number(4.2),
+            Token::Number(-4.2),
+            Token::Number(0.4),
+            Token::Number(5.0),
+            Token::Number(1e-7),
+            Token::Number(-2500.0),
+            Token::Number(50.0),
+            Token::Number(3.0),
+        ],
+        tokenize("4.2 +4.2 -4.2 .4 5. 1e-7 -2.5E+3 .5e2 3e0")
+    );
+
+    // Numbers end at a delimiter, with or without whitespace.
+    assert_eq!(
+        vec![
+            Token::ParenOpen,
+            Token::Number(1.0),
+            Token::Comma,
+            Token::Number(-2.0),
+            Token::ParenClose,
+        ],
+        tokenize("(1,-2)")
+    );
+
+    let non_finite = tokenize("NaN -inf +Infinity INF");
+    assert!(match non_finite[0] {
+        Token::Number(n) => n.is_nan(),
+        _ => false,
+    });
+    assert_eq!(
+        vec![
+            Token::Number(f64::NEG_INFINITY),
+            Token::Number(f64::INFINITY),
+            Token::Number(f64::INFINITY),
+        ],
+        non_finite[1..].to_vec()
+    );
+}
+
+#[test]
+fn invalid_numbers() {
+    let invalid = [
+        "1-2", "--5", "+", "1.2.3", "-.e", "++5", "1e", "1e+", "2E-x", "1abc", "-nope", ".",
+    ];
+    for input in invalid.iter() {
+        match Tokens::new(input).next() {
+            Some(Err(Error::InvalidNumber { ref found, .. })) => assert_eq!(input, found),
+            other => panic!(
+                "expected an invalid number for {:?}, found {:?}",
+                input, other
+            ),
+        }
+    }
+
+    // The error covers the text up to the next delimiter, and tokenizing carries on after it.
+    let mut tokens = Tokens::new("(1x2, 3)");
+    assert_eq!(Some(Ok(Token::ParenOpen)), tokens.next());
+    assert!(tokens.next().unwrap().is_err());
+    assert_eq!(Span { start: 1, end: 4 }, tokens.last_span());
+    assert_eq!(Some(Ok(Token::Comma)), tokens.next());
+    assert_eq!(Some(Ok(Token::Number(3.0))), tokens.next());
+}
+
+#[test]
+fn kinds_and_lookahead() {
+    let tokens = Tokens::new("POINT (1 2)");
+    assert_eq!(TokenKind::Word, tokens.peek().unwrap().unwrap().kind());
+
+    // Looking ahead further by cloning the stream.
+    let mut ahead = tokens.clone();
+    ahead.next();
+    assert_eq!(Some(Ok(Token::ParenOpen)), ahead.next());
+    assert_eq!(Span { start: 6, end: 7 }, ahead.last_span());
+
+    let kinds: Vec<TokenKind> = tokens.map(|token| token.unwrap().kind()).collect();
+    assert_eq!(
+        vec![
+            TokenKind::Word,
+            TokenKind::ParenOpen,
+            TokenKind::Number,
+            TokenKind::Number,
+            TokenKind::ParenClose,
+        ],
+        kinds
+    );
+}
+
+#[test]
+fn peekable_tokens() {
+    let input = "ID[\"x\", (1)]";
+    let mut tokens = PeekableTokens::new(Tokens::new(input));
+    assert_eq!(Ok(Some(&Token::Word("ID"))), tokens.peek());
+    let ahead: Vec<TokenKind> = tokens
+        .lookahead()
+        .take(3)
+        .map(|token| token.unwrap().kind())
+        .collect();
+    assert_eq!(
+        vec![TokenKind::Word, TokenKind::Word, TokenKind::Word],
+        ahead
+    );
+    assert_eq!(Ok(Some(Token::Word("ID"))), tokens.next());
+    assert_eq!("ID", tokens.slice(tokens.last_span()));
+    assert_eq!(Ok(Some(Token::Word("["))), tokens.next());
+    assert_eq!(Ok(Some(Token::Word("\""))), tokens.next());
+    while tokens.next() != Ok(Some(Token::ParenOpen)) {}
+    assert_eq!(1, tokens.paren_depth());
+    assert_eq!(Ok(Some(Token::Number(1.0))), tokens.next());
+    assert_eq!(Ok(Some(Token::ParenClose)), tokens.next());
+    assert_eq!(0, tokens.paren_depth());
+    assert_eq!(Ok(Some(Token::Word("]"))), tokens.next());
+    assert_eq!(Ok(None), tokens.next());
+
+    let options = ParseOptions {
+        max_input_len: Some(4),
+        ..Default::default()
+    };
+    let mut tokens = PeekableTokens::with_options(Tokens::new("POINT"), options);
+    match tokens.next() {
+        Err(Error::LimitExceeded {
+            limit: Limit::InputLen,
+            ..
+        }) => (),
+        other => panic!("expected the input length limit, found {:?}", other),
+    }
+}
@@ -0,0 +1,57 @@
+// Copyright 2015 The GeoRust Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "geo")]
+
+extern crate geo;
+extern crate wkt;
+
+use wkt::types::{Coord, Point};
+use wkt::{Geometry, ToWkt, Wkt};
+
+/// A type of a crate using `wkt`, implementing `ToWkt` itself.
+struct Station {
+    x: f64,
+    y: f64,
+}
+
+impl ToWkt for Station {
+    fn to_wkt(&self) -> Wkt {
+        let coord = Coord {
+            x: self.x,
+            y: self.y,
+            z: None,
+            m: None,
+        };
+        let mut wkt = Wkt::new();
+        wkt.add_item(Geometry::Point(Point(Some(coord))));
+        wkt
+    }
+}
+
+#[test]
+fn trait_objects() {
+    let point = geo::Point(geo::Coordinate { x: 1., y: 2. });
+    let values: Vec<Box<dyn ToWkt>> = vec![
+        Box::new(point),
+        Box::new(geo::LineString(vec![point, point])),
+        Box::new(Station { x: 3., y: 4. }),
+    ];
+    let written: Vec<String> = values.iter().map(|value| value.to_wkt_string()).collect();
+    assert_eq!(
+        vec!["POINT (1 2)", "LINESTRING (1 2, 1 2)", "POINT (3 4)"],
+        written
+    );
+    assert_eq!(1, values[2].to_wkt().items.len());
+}